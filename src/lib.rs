@@ -0,0 +1,4137 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::env;
+use std::path::{Path, PathBuf};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DOSAGE_UNITS: &[(&str, &str)] = &[
+    ("mg", "mg"),
+    ("milligram", "mg"),
+    ("milligrams", "mg"),
+    ("mcg", "mcg"),
+    ("microgram", "mcg"),
+    ("micrograms", "mcg"),
+    ("ml", "ml"),
+    ("milliliter", "ml"),
+    ("milliliters", "ml"),
+    ("iu", "IU"),
+    ("pill", "pill"),
+    ("pills", "pill"),
+    ("tablet", "tablet"),
+    ("tablets", "tablet"),
+    ("tab", "tablet"),
+    ("tabs", "tablet"),
+];
+const DATE_INPUT_FORMATS: [&str; 7] = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%d %b %Y", "%d %B %Y", "%b %d, %Y", "%B %d, %Y"];
+const TIME_FORMAT: &str = "%H:%M:%S";
+const CLOCK_FORMAT: &str = "%H:%M";
+const DEFAULT_REFILL_THRESHOLD: u32 = 7;
+const PATIENT_REGISTRY_FILE: &str = "patients.txt";
+const PATIENT_FILE_SUFFIXES: [&str; 8] = ["meds.txt", "logs.txt", "snoozes.txt", "nags.txt", "data.json", "audit.txt", "config.txt", "groups.txt"];
+const REPORT_FILE_PREFIXES: [&str; 5] = ["weekly_report_", "monthly_report_", "clinical_report_", "reminders_", "logs_archive_"];
+const DEFAULT_GRACE_MINUTES: u32 = 60;
+const DEFAULT_STARTING_QUANTITY: u32 = 30;
+const MAX_UNDO_HISTORY: usize = 10;
+const COURSE_ENDED_GRACE_DAYS: i64 = 3;
+const MIN_TIME_SAMPLES: usize = 5;
+const MIN_SCHEDULE_SHIFT_MINUTES: u32 = 15;
+pub const STALE_AFTER_DAYS: u32 = 5;
+pub const TIME_BUCKETS: [&str; 5] = ["Morning", "Afternoon", "Evening", "Bedtime", "As needed"];
+/// Clock window (start, end in minutes) during which reminders for each bucket are shown.
+/// Windows overlap so a late dose stays visible for a while, and Bedtime wraps past midnight.
+const REMINDER_WINDOWS: [(&str, u32, u32); 4] = [
+    ("Morning", 4 * 60, 13 * 60),
+    ("Afternoon", 11 * 60, 18 * 60),
+    ("Evening", 16 * 60, 22 * 60),
+    ("Bedtime", 19 * 60, 3 * 60),
+];
+
+const DRUG_ALIASES: &[(&str, &str)] = &[
+    ("coumadin", "warfarin"),
+    ("jantoven", "warfarin"),
+    ("bayer", "aspirin"),
+    ("ecotrin", "aspirin"),
+    ("advil", "ibuprofen"),
+    ("motrin", "ibuprofen"),
+    ("aleve", "naproxen"),
+    ("plavix", "clopidogrel"),
+    ("prilosec", "omeprazole"),
+    ("zestril", "lisinopril"),
+    ("prinivil", "lisinopril"),
+    ("aldactone", "spironolactone"),
+    ("lanoxin", "digoxin"),
+    ("cordarone", "amiodarone"),
+    ("pacerone", "amiodarone"),
+    ("zocor", "simvastatin"),
+    ("viagra", "sildenafil"),
+    ("nitrostat", "nitroglycerin"),
+];
+
+const DRUG_INTERACTIONS: &[(&str, &str, &str)] = &[
+    ("warfarin", "aspirin", "Increased risk of bleeding"),
+    ("warfarin", "ibuprofen", "Increased risk of bleeding"),
+    ("warfarin", "naproxen", "Increased risk of bleeding"),
+    ("clopidogrel", "omeprazole", "Omeprazole may reduce the effect of clopidogrel"),
+    ("lisinopril", "spironolactone", "Risk of high potassium levels"),
+    ("digoxin", "amiodarone", "Amiodarone can raise digoxin to toxic levels"),
+    ("simvastatin", "amiodarone", "Increased risk of muscle damage"),
+    ("sildenafil", "nitroglycerin", "Dangerous drop in blood pressure"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Named(String),
+    Clock(u8, u8),
+}
+
+impl Schedule {
+    pub fn clock_time(&self) -> Option<(u8, u8)> {
+        match self {
+            Schedule::Clock(hour, minute) => Some((*hour, *minute)),
+            Schedule::Named(name) => match name.as_str() {
+                "Morning" => Some((8, 0)),
+                "Afternoon" => Some((13, 0)),
+                "Evening" => Some((18, 0)),
+                "Bedtime" => Some((21, 0)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Schedule::Named(name) => write!(f, "{}", name),
+            Schedule::Clock(hour, minute) => write!(f, "{:02}:{:02}", hour, minute),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Priority::High => write!(f, "High"),
+            Priority::Normal => write!(f, "Normal"),
+            Priority::Low => write!(f, "Low"),
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "high" => Ok(Priority::High),
+            "normal" => Ok(Priority::Normal),
+            "low" => Ok(Priority::Low),
+            _ => Err(format!("Unknown priority '{}'", input.trim())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum Frequency {
+    #[default]
+    Daily,
+    WeeklyOn(Vec<Weekday>),
+    EveryNDays(u32),
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "Daily"),
+            Frequency::WeeklyOn(days) => {
+                let days: Vec<String> = days.iter().map(|day| day.to_string()).collect();
+                write!(f, "Weekly on {}", days.join(", "))
+            }
+            Frequency::EveryNDays(n) => write!(f, "Every {} days", n),
+        }
+    }
+}
+
+impl std::str::FromStr for Frequency {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let lower = input.to_lowercase();
+        if lower.is_empty() || lower == "daily" {
+            return Ok(Frequency::Daily);
+        }
+        if let Some(rest) = lower.strip_prefix("every") {
+            let n = rest.trim().trim_end_matches("days").trim_end_matches("day").trim();
+            return match n.parse::<u32>() {
+                Ok(n) if n > 0 => Ok(if n == 1 { Frequency::Daily } else { Frequency::EveryNDays(n) }),
+                _ => Err(format!("Invalid interval in '{}'", input)),
+            };
+        }
+        let rest = lower.strip_prefix("weekly on").unwrap_or(&lower);
+        let mut days = Vec::new();
+        for token in rest.split(|c: char| c == ',' || c == '+' || c.is_whitespace()).filter(|t| !t.is_empty() && *t != "and") {
+            let day = token.parse::<Weekday>().map_err(|_| format!("Unknown weekday '{}'", token))?;
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+        if days.is_empty() {
+            return Err(format!("Unknown frequency '{}'", input));
+        }
+        days.sort_by_key(|day| day.num_days_from_monday());
+        Ok(Frequency::WeeklyOn(days))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "en" | "english" => Ok(Locale::English),
+            "es" | "spanish" | "español" | "espanol" => Ok(Locale::Spanish),
+            _ => Err(format!("Unknown language '{}'. Use en or es", input.trim())),
+        }
+    }
+}
+
+/// Spanish text keyed by the English original, so anything missing here falls back to English.
+const SPANISH: &[(&str, &str)] = &[
+    ("Morning", "Mañana"),
+    ("Afternoon", "Tarde"),
+    ("Evening", "Noche"),
+    ("Bedtime", "Al acostarse"),
+    ("As needed", "Según sea necesario"),
+    ("{med} at {time}", "{med} - {time}"),
+    ("{taken}/{total} doses taken", "{taken}/{total} dosis tomadas"),
+    ("CRITICAL: ", "CRÍTICO: "),
+    ("IMPORTANT: {med} still not taken (due {time})", "IMPORTANTE: {med} aún no se ha tomado (hora {time})"),
+    ("URGENT: {med} still not taken (due {time})", "URGENTE: {med} aún no se ha tomado (hora {time})"),
+    ("URGENT: {med} overdue {hours} hour", "URGENTE: {med} con {hours} hora de retraso"),
+    ("URGENT: {med} overdue {hours} hours", "URGENTE: {med} con {hours} horas de retraso"),
+    ("WEEKLY SUMMARY FOR", "RESUMEN SEMANAL DE"),
+    ("Week starting:", "Semana que empieza:"),
+    ("MEDICATION:", "MEDICAMENTO:"),
+    ("Daily Record:", "Registro diario:"),
+    ("DAILY OVERVIEW:", "RESUMEN DIARIO:"),
+    ("nothing recorded yet", "sin registros todavía"),
+    ("NOT YET RECORDED:", "SIN REGISTRAR:"),
+    ("Hello", "Hola"),
+    ("TODAY", "HOY"),
+    ("OVERDUE - Please take as soon as possible:", "ATRASADO - Tómelo lo antes posible:"),
+    ("DUE NOW - Please take:", "AHORA - Por favor tome:"),
+    ("Next", "Siguiente"),
+    ("Coming up later today:", "Más tarde hoy:"),
+    ("No medications scheduled.", "No hay medicamentos programados."),
+    ("All medications taken today!", "¡Todos los medicamentos de hoy tomados!"),
+    ("MENU:", "MENÚ:"),
+    ("Choice", "Opción"),
+    ("Goodbye!", "¡Adiós!"),
+    ("Invalid choice.", "Opción no válida."),
+    ("View Today's Medications", "Ver medicamentos de hoy"),
+    ("Mark Medication as Taken", "Marcar medicamento como tomado"),
+    ("Mark Medication as Missed", "Marcar medicamento como no tomado"),
+    ("View All Medications", "Ver todos los medicamentos"),
+    ("Add New Medication", "Agregar medicamento nuevo"),
+    ("Refill Medication", "Reponer medicamento"),
+    ("View Weekly Summary", "Ver resumen semanal"),
+    ("Save Weekly Report to File", "Guardar informe semanal en archivo"),
+    ("Export Weekly Report as CSV", "Exportar informe semanal como CSV"),
+    ("Remove Medication", "Eliminar medicamento"),
+    ("Edit Medication", "Editar medicamento"),
+    ("Switch to JSON Storage", "Cambiar a almacenamiento JSON"),
+    ("View Adherence for Date Range", "Ver cumplimiento por fechas"),
+    ("Undo Last Action", "Deshacer última acción"),
+    ("Save Monthly Report to File", "Guardar informe mensual en archivo"),
+    ("Search Medications", "Buscar medicamentos"),
+    ("Mark ALL as Taken", "Marcar TODOS como tomados"),
+    ("Snooze a Reminder", "Posponer un recordatorio"),
+    ("Export Reminders to Calendar (.ics)", "Exportar recordatorios al calendario (.ics)"),
+    ("Switch Patient", "Cambiar de paciente"),
+    ("Backup Data", "Hacer copia de seguridad"),
+    ("Restore from Backup", "Restaurar copia de seguridad"),
+    ("Lifetime Statistics", "Estadísticas generales"),
+    ("View Recent Activity", "Ver actividad reciente"),
+    ("Move Logged Doses to Another Date", "Mover dosis registradas a otra fecha"),
+    ("Days with Missed Doses", "Días con dosis olvidadas"),
+    ("Archive Old Logs", "Archivar registros antiguos"),
+    ("Copy Regimen to Another Patient", "Copiar tratamiento a otro paciente"),
+    ("Dismiss a Reminder", "Descartar un recordatorio"),
+    ("Reset a Day's Log", "Borrar el registro de un día"),
+    ("Import Medications from CSV", "Importar medicamentos desde CSV"),
+    ("Adherence by Time of Day", "Cumplimiento por momento del día"),
+    ("Log a Dose for a Different Date", "Registrar una dosis en otra fecha"),
+    ("Check & Repair Data", "Revisar y reparar datos"),
+    ("Pause or Resume a Medication", "Pausar o reanudar un medicamento"),
+    ("Set Default Starting Quantity", "Cantidad inicial predeterminada"),
+    ("Catch Up on Missed Days", "Ponerse al día con días sin registrar"),
+    ("Save Clinical Report", "Guardar informe clínico"),
+    ("Medication Groups", "Grupos de medicamentos"),
+    ("Refill All", "Reponer todos"),
+    ("Set Week Start Day", "Día de inicio de la semana"),
+    ("No medications on record for this patient.", "No hay medicamentos registrados para este paciente."),
+    ("Exit", "Salir"),
+];
+
+pub fn tr(locale: Locale, text: &'static str) -> &'static str {
+    match locale {
+        Locale::English => text,
+        Locale::Spanish => SPANISH.iter().find(|(english, _)| *english == text).map_or(text, |(_, spanish)| spanish),
+    }
+}
+
+/// Display name for a stored time of day. Bucket names are translated; clock times pass through.
+pub fn localize_time_of_day(locale: Locale, time_of_day: &str) -> String {
+    match TIME_BUCKETS.iter().find(|bucket| bucket.eq_ignore_ascii_case(time_of_day.trim())) {
+        Some(bucket) => tr(locale, bucket).to_string(),
+        None => time_of_day.to_string(),
+    }
+}
+
+/// A pill count in hundredths of a unit, so half and quarter tablets add up exactly instead of
+/// drifting the way repeated `f32` arithmetic would. Stored and displayed as a decimal ("4.5").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "f64", into = "f64")]
+pub struct Quantity(u32);
+
+impl Quantity {
+    pub const ZERO: Quantity = Quantity(0);
+    pub const ONE: Quantity = Quantity(100);
+
+    pub fn whole(units: u32) -> Self {
+        Quantity(units.saturating_mul(100))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: Quantity) -> Option<Quantity> {
+        self.0.checked_add(other.0).map(Quantity)
+    }
+
+    pub fn saturating_add(self, other: Quantity) -> Quantity {
+        Quantity(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Quantity) -> Quantity {
+        Quantity(self.0.saturating_sub(other.0))
+    }
+
+    pub fn saturating_mul(self, times: u32) -> Quantity {
+        Quantity(self.0.saturating_mul(times))
+    }
+
+    /// How many whole times `per` fits into this quantity.
+    pub fn times(self, per: Quantity) -> u32 {
+        self.0.checked_div(per.0).unwrap_or(0)
+    }
+}
+
+impl From<u32> for Quantity {
+    fn from(units: u32) -> Self {
+        Quantity::whole(units)
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(units: f64) -> Self {
+        Quantity((units.max(0.0) * 100.0).round() as u32)
+    }
+}
+
+impl From<Quantity> for f64 {
+    fn from(quantity: Quantity) -> Self {
+        quantity.0 as f64 / 100.0
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (whole, fraction) = (self.0 / 100, self.0 % 100);
+        match fraction {
+            0 => write!(f, "{}", whole),
+            _ if fraction % 10 == 0 => write!(f, "{}.{}", whole, fraction / 10),
+            _ => write!(f, "{}.{:02}", whole, fraction),
+        }
+    }
+}
+
+impl std::str::FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let invalid = || format!("'{}' is not a valid quantity. Enter a number like 30 or 0.5.", input);
+        let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+        if fraction.len() > 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let whole: u32 = if whole.is_empty() && !fraction.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+        let hundredths = format!("{:0<2}", fraction).parse::<u32>().map_err(|_| invalid())?;
+        whole.checked_mul(100)
+            .and_then(|whole| whole.checked_add(hundredths))
+            .map(Quantity)
+            .ok_or_else(invalid)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Medication {
+    pub name: String,
+    pub dosage: String,
+    pub time_of_day: String,
+    pub current_count: Quantity,
+    pub total_prescribed: Quantity,
+    pub doses_per_day: u32,
+    pub refill_threshold: u32,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub added_on: String,
+    #[serde(default)]
+    pub max_count: Option<u32>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub is_prn: bool,
+    #[serde(default)]
+    pub frequency: Frequency,
+    /// Price of one pill or unit; a dose costs this times `units_per_dose`.
+    #[serde(default)]
+    pub cost_per_unit: Option<f32>,
+    #[serde(default = "default_grace_minutes")]
+    pub grace_minutes: u32,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default = "default_units_per_dose")]
+    pub units_per_dose: Quantity,
+    /// Pills per standard pharmacy refill; 0 means refills aren't being tracked for this med.
+    #[serde(default)]
+    pub refill_size: u32,
+    #[serde(default)]
+    pub refills_remaining: u32,
+    /// First and last day of a course (e.g. a 10-day antibiotic); `None` leaves that side open.
+    #[serde(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// Each pause as `(first day paused, day resumed)`; the last one stays open while `paused`.
+    #[serde(default)]
+    pub pauses: Vec<(String, Option<String>)>,
+    /// `dosage` as an amount and unit, or `None` when it is free text `parse_dosage` can't read.
+    #[serde(default)]
+    pub parsed_dosage: Option<Dosage>,
+    /// First due day of an every-N-days schedule; the interval counts from here.
+    #[serde(default)]
+    pub interval_start: Option<String>,
+}
+
+impl TryFrom<&str> for Medication {
+    type Error = String;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        let parts = parse_csv_line(line);
+        let problem = if parts.len() < 5 {
+            Some(format!("expected at least 5 fields, found {}", parts.len()))
+        } else if parts[0].trim().is_empty() {
+            Some("missing medication name".to_string())
+        } else if parts[3].parse::<Quantity>().is_err() || parts[4].parse::<Quantity>().is_err() {
+            Some("pill counts are not numbers".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(problem);
+        }
+        
+        let mut med = Medication {
+            name: parts[0].clone(),
+            dosage: parts[1].clone(),
+            time_of_day: parts[2].clone(),
+            current_count: parts[3].parse().unwrap_or_default(),
+            total_prescribed: parts[4].parse().unwrap_or_default(),
+            doses_per_day: parts.get(5).and_then(|v| v.parse().ok()).unwrap_or(1).max(1),
+            refill_threshold: parts.get(6).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_REFILL_THRESHOLD),
+            notes: parts.get(7).cloned().unwrap_or_default(),
+            added_on: parts.get(8).cloned().unwrap_or_default(),
+            max_count: parts.get(9).and_then(|v| v.parse().ok()),
+            priority: parts.get(10).and_then(|v| v.parse().ok()).unwrap_or_default(),
+            is_prn: parts.get(11).is_some_and(|v| v == "1") || is_as_needed(&parts[2]),
+            frequency: parts.get(12).and_then(|value| value.parse().ok()).unwrap_or_default(),
+            cost_per_unit: parts.get(13).and_then(|value| value.parse().ok()),
+            grace_minutes: parts.get(14).and_then(|value| value.parse().ok()).unwrap_or_else(default_grace_minutes),
+            paused: parts.get(15).is_some_and(|v| v == "1"),
+            units_per_dose: parts.get(16).and_then(|value| value.parse().ok()).unwrap_or_else(default_units_per_dose),
+            refill_size: parts.get(17).and_then(|value| value.parse().ok()).unwrap_or(0),
+            refills_remaining: parts.get(18).and_then(|value| value.parse().ok()).unwrap_or(0),
+            start_date: parts.get(19).filter(|value| !value.is_empty()).cloned(),
+            end_date: parts.get(20).filter(|value| !value.is_empty()).cloned(),
+            pauses: parts.get(21)
+                .map(|value| value.split(';')
+                    .filter_map(|pause| pause.split_once(':'))
+                    .map(|(from, until)| (from.to_string(), Some(until.to_string()).filter(|until| !until.is_empty())))
+                    .collect())
+                .unwrap_or_default(),
+            parsed_dosage: match parts.get(22) {
+                Some(value) => value.split_once(' ')
+                    .and_then(|(amount, unit)| Some(Dosage { amount: amount.parse().ok()?, unit: unit.to_string() })),
+                None => parse_dosage(&parts[1]).ok(),
+            },
+            interval_start: parts.get(23).filter(|value| !value.is_empty()).cloned(),
+        };
+        // An interval needs a day to count from; with no usable one, start counting today.
+        if matches!(med.frequency, Frequency::EveryNDays(_)) && med.interval_start.is_none()
+            && NaiveDate::parse_from_str(&med.added_on, DATE_FORMAT).is_err() {
+            med.interval_start = Some(get_today());
+        }
+        Ok(med)
+    }
+}
+
+impl fmt::Display for Medication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {} at {}, {}x daily ({} left)",
+            self.name, self.dosage, self.time_of_day, self.doses_per_day, self.current_count)?;
+        if self.paused {
+            write!(f, " - paused")?;
+        } else if self.is_prn {
+            write!(f, " - as needed")?;
+        } else if self.frequency != Frequency::Daily {
+            write!(f, " - {}", self.frequency)?;
+        }
+        match (&self.start_date, &self.end_date) {
+            (Some(start), Some(end)) => write!(f, " - {} to {}", start, end)?,
+            (Some(start), None) => write!(f, " - from {}", start)?,
+            (None, Some(end)) => write!(f, " - until {}", end)?,
+            (None, None) => {}
+        }
+        if self.priority != Priority::Normal {
+            write!(f, " - {} priority", self.priority)?;
+        }
+        if !self.notes.is_empty() {
+            write!(f, " - Note: {}", self.notes)?;
+        }
+        Ok(())
+    }
+}
+
+impl Medication {
+    /// One line of the meds file, without the trailing newline. `TryFrom<&str>` reads it back.
+    pub fn to_csv_line(&self) -> String {
+        let pauses: Vec<String> = self.pauses.iter()
+            .map(|(from, until)| format!("{}:{}", from, until.as_deref().unwrap_or("")))
+            .collect();
+        format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            escape_csv_field(&self.name),
+            escape_csv_field(&self.dosage),
+            escape_csv_field(&self.time_of_day),
+            self.current_count,
+            self.total_prescribed,
+            self.doses_per_day,
+            self.refill_threshold,
+            escape_csv_field(&self.notes),
+            self.added_on,
+            self.max_count.map(|max| max.to_string()).unwrap_or_default(),
+            self.priority,
+            if self.is_prn { "1" } else { "0" },
+            escape_csv_field(&self.frequency.to_string()),
+            self.cost_per_unit.map(|cost| cost.to_string()).unwrap_or_default(),
+            self.grace_minutes,
+            if self.paused { "1" } else { "0" },
+            self.units_per_dose,
+            self.refill_size,
+            self.refills_remaining,
+            self.start_date.as_deref().unwrap_or(""),
+            self.end_date.as_deref().unwrap_or(""),
+            pauses.join(";"),
+            self.parsed_dosage.as_ref().map(|dosage| format!("{} {}", dosage.amount, dosage.unit)).unwrap_or_default(),
+            self.interval_start.as_deref().unwrap_or(""),
+        )
+    }
+
+    pub fn in_course(&self, date: &str) -> bool {
+        self.start_date.as_deref().is_none_or(|start| date >= start)
+            && self.end_date.as_deref().is_none_or(|end| date <= end)
+    }
+
+    /// What one dose costs: the price of a single unit times the units taken per dose.
+    pub fn cost_per_dose(&self) -> Option<f32> {
+        self.cost_per_unit.map(|cost| cost * f64::from(self.units_per_dose) as f32)
+    }
+
+    pub fn doses_per_month(&self) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let due_days = match &self.frequency {
+            Frequency::Daily => 30.0,
+            Frequency::WeeklyOn(days) => days.len() as f32 * 30.0 / 7.0,
+            Frequency::EveryNDays(n) => 30.0 / (*n).max(1) as f32,
+        };
+        due_days * self.doses_per_day as f32
+    }
+
+    /// Whether `date` falls inside a pause. A med paused before pauses were dated counts as
+    /// paused on every day.
+    pub fn paused_on(&self, date: &str) -> bool {
+        if self.paused && self.pauses.is_empty() {
+            return true;
+        }
+        self.pauses.iter().any(|(from, until)| date >= from.as_str() && until.as_deref().is_none_or(|until| date < until))
+    }
+
+    /// Days inside a pause, and dates outside a course's start/end, are never due, so they drop
+    /// out of reminders and adherence. Days before or after a pause keep counting.
+    pub fn is_due_on(&self, date: &str) -> bool {
+        if self.paused_on(date) || !self.in_course(date) {
+            return false;
+        }
+        let Ok(day) = NaiveDate::parse_from_str(date, DATE_FORMAT) else {
+            return true;
+        };
+        match &self.frequency {
+            Frequency::Daily => true,
+            Frequency::WeeklyOn(days) => days.contains(&day.weekday()),
+            // Schedules saved before `interval_start` existed count from the day the med was added.
+            Frequency::EveryNDays(n) => match NaiveDate::parse_from_str(self.interval_start.as_deref().unwrap_or(&self.added_on), DATE_FORMAT) {
+                Ok(start) => (day - start).num_days().rem_euclid((*n).max(1) as i64) == 0,
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DoseStatus {
+    Taken,
+    Missed,
+    Skipped(String),
+}
+
+impl DoseStatus {
+    fn precedence(&self) -> u8 {
+        match self {
+            DoseStatus::Taken => 2,
+            DoseStatus::Skipped(_) => 1,
+            DoseStatus::Missed => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dosage {
+    pub amount: f32,
+    pub unit: String,
+}
+
+impl fmt::Display for Dosage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = self.amount != 1.0 && (self.unit == "pill" || self.unit == "tablet");
+        write!(f, "{} {}{}", self.amount, self.unit, if plural { "s" } else { "" })
+    }
+}
+
+/// Receives dose reminders as they are shown, so front-ends (desktop notifications, speech for
+/// low-vision users) can present them without changes to the tracker.
+pub trait ReminderSink {
+    fn notify(&self, med: &str, message: &str);
+}
+
+/// Prints reminders as dashboard bullet points.
+pub struct ConsoleSink;
+
+impl ReminderSink for ConsoleSink {
+    fn notify(&self, _med: &str, message: &str) {
+        println!("   * {}", message);
+    }
+}
+
+/// One medication's state for a day, without any display formatting. `DoseStatus` already names
+/// the per-dose taken/missed/skipped state, hence the different name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MedicationStatus {
+    pub name: String,
+    pub dosage: String,
+    pub time_of_day: String,
+    pub priority: Priority,
+    pub is_prn: bool,
+    pub doses_taken: u32,
+    pub doses_per_day: u32,
+    pub taken: bool,
+    pub reminder_needed: bool,
+    pub snoozed_until: Option<String>,
+    pub last_taken_time: Option<String>,
+    pub late: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkOutcome {
+    Recorded,
+    OutOfStock,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredDoseRecord")]
+pub struct DoseRecord {
+    pub status: DoseStatus,
+    pub time: Option<String>,
+    /// Who gave the dose when it wasn't the patient. `None` means self-administered.
+    pub administered_by: Option<String>,
+    /// Whether taking this dose came out of the supply. False for doses logged while out of stock,
+    /// so un-marking them doesn't add back pills that were never there.
+    pub deducted: bool,
+}
+
+impl DoseRecord {
+    pub fn administered_by(&self) -> &str {
+        self.administered_by.as_deref().unwrap_or("self")
+    }
+
+    pub fn is_taken(&self) -> bool {
+        self.status == DoseStatus::Taken
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        matches!(self.status, DoseStatus::Skipped(_))
+    }
+}
+
+#[derive(Deserialize)]
+struct StoredDoseRecord {
+    #[serde(default)]
+    status: Option<DoseStatus>,
+    #[serde(default)]
+    taken: bool,
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    administered_by: Option<String>,
+    #[serde(default)]
+    deducted: Option<bool>,
+}
+
+impl From<StoredDoseRecord> for DoseRecord {
+    fn from(stored: StoredDoseRecord) -> Self {
+        let status = match stored.status {
+            Some(status) => status,
+            None if stored.taken => DoseStatus::Taken,
+            None => DoseStatus::Missed,
+        };
+        let deducted = status == DoseStatus::Taken && stored.deducted.unwrap_or(true);
+        DoseRecord { status, time: stored.time, administered_by: stored.administered_by, deducted }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyLog {
+    pub date: String,
+    pub taken: HashMap<String, HashMap<u32, DoseRecord>>,
+    #[serde(default)]
+    pub snoozed_until: HashMap<String, String>,
+    #[serde(default)]
+    pub reminders_shown: HashMap<String, u32>,
+    #[serde(default)]
+    pub acknowledged: HashSet<String>,
+}
+
+impl DailyLog {
+    pub fn new(date: &str) -> Self {
+        DailyLog {
+            date: date.to_string(),
+            taken: HashMap::new(),
+            snoozed_until: HashMap::new(),
+            reminders_shown: HashMap::new(),
+            acknowledged: HashSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.taken.is_empty() && self.snoozed_until.is_empty() && self.reminders_shown.is_empty() && self.acknowledged.is_empty()
+    }
+}
+
+impl fmt::Display for DailyLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (mut taken, mut missed, mut skipped) = (0, 0, 0);
+        for record in self.taken.values().flat_map(|doses| doses.values()) {
+            match record.status {
+                DoseStatus::Taken => taken += 1,
+                DoseStatus::Missed => missed += 1,
+                DoseStatus::Skipped(_) => skipped += 1,
+            }
+        }
+        write!(f, "{}: {} taken, {} missed, {} skipped across {} medication{}",
+            self.date, taken, missed, skipped, self.taken.len(), if self.taken.len() == 1 { "" } else { "s" })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MarkAction {
+    med_name: String,
+    date: String,
+    dose: u32,
+    previous: Option<DoseRecord>,
+    count_change: i32,
+    amount: Quantity,
+}
+
+/// Marks a patient's data as in use by this process; the lock file is removed on drop.
+struct DataLock {
+    path: PathBuf,
+}
+
+impl DataLock {
+    fn acquire(path: PathBuf, patient_name: &str) -> io::Result<Self> {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                writeln!(file, "{}", std::process::id())?;
+                Ok(DataLock { path })
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && lock_is_stale(&path) => {
+                fs::remove_file(&path)?;
+                DataLock::acquire(path, patient_name)
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("{}'s data is in use by another session (delete {} if that session has closed)", patient_name, path.display()),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A lock left behind by a process that has since exited (crash, closed terminal). Only
+/// detectable where /proc exists; elsewhere the lock is assumed live.
+fn lock_is_stale(path: &Path) -> bool {
+    let Some(pid) = fs::read_to_string(path).ok().and_then(|pid| pid.trim().parse::<u32>().ok()) else {
+        return false;
+    };
+    pid != std::process::id() && Path::new("/proc/self").exists() && !Path::new("/proc").join(pid.to_string()).exists()
+}
+
+impl Drop for DataLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Serialize)]
+struct LogEntryJson<'a> {
+    date: &'a str,
+    medication: &'a str,
+    dose: u32,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    administered_by: Option<&'a str>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrackerData {
+    medications: HashMap<String, Medication>,
+    daily_logs: HashMap<String, DailyLog>,
+}
+
+pub struct MedicationTracker {
+    medications: HashMap<String, Medication>,
+    daily_logs: HashMap<String, DailyLog>,
+    patient_name: String,
+    file_stem: String,
+    data_dir: PathBuf,
+    data_file: PathBuf,
+    log_file: PathBuf,
+    snooze_file: PathBuf,
+    nag_file: PathBuf,
+    audit_file: PathBuf,
+    config_file: PathBuf,
+    group_file: PathBuf,
+    json_file: PathBuf,
+    json_storage: bool,
+    default_quantity: u32,
+    week_start_day: Weekday,
+    groups: HashMap<String, Vec<String>>,
+    history: Vec<MarkAction>,
+    display_order: Vec<String>,
+    load_warnings: Vec<String>,
+    import_errors: Vec<String>,
+    immediate_persistence: bool,
+    locale: Locale,
+    data_dirty: Cell<bool>,
+    logs_dirty: Cell<bool>,
+    pending_audit: RefCell<Vec<String>>,
+    /// Whether patients.txt lists this patient; set once their first file is written.
+    registered: Cell<bool>,
+    lock: Option<DataLock>,
+}
+
+impl MedicationTracker {
+    pub fn new(patient_name: &str) -> io::Result<Self> {
+        Self::with_data_dir(patient_name, &default_data_dir(patient_name))
+    }
+
+    pub fn with_data_dir(patient_name: &str, dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        
+        let (file_stem, registered) = patient_file_stem(dir, patient_name)?;
+        let lock = DataLock::acquire(dir.join(format!("{}.lock", file_stem)), patient_name)?;
+        migrate_legacy_files(dir, patient_name, &file_stem)?;
+        
+        let data_file = dir.join(format!("{}_meds.txt", file_stem));
+        let log_file = dir.join(format!("{}_logs.txt", file_stem));
+        let snooze_file = dir.join(format!("{}_snoozes.txt", file_stem));
+        let nag_file = dir.join(format!("{}_nags.txt", file_stem));
+        let audit_file = dir.join(format!("{}_audit.txt", file_stem));
+        let config_file = dir.join(format!("{}_config.txt", file_stem));
+        let group_file = dir.join(format!("{}_groups.txt", file_stem));
+        let json_file = dir.join(format!("{}_data.json", file_stem));
+        let json_storage = json_file.exists();
+        
+        let mut tracker = MedicationTracker {
+            medications: HashMap::new(),
+            daily_logs: HashMap::new(),
+            patient_name: patient_name.to_string(),
+            file_stem,
+            data_dir: dir.to_path_buf(),
+            data_file,
+            log_file,
+            snooze_file,
+            nag_file,
+            audit_file,
+            config_file,
+            group_file,
+            json_file,
+            json_storage,
+            default_quantity: DEFAULT_STARTING_QUANTITY,
+            week_start_day: Weekday::Mon,
+            groups: HashMap::new(),
+            history: Vec::new(),
+            display_order: Vec::new(),
+            load_warnings: Vec::new(),
+            import_errors: Vec::new(),
+            immediate_persistence: false,
+            locale: Locale::default(),
+            data_dirty: Cell::new(false),
+            logs_dirty: Cell::new(false),
+            pending_audit: RefCell::new(Vec::new()),
+            registered: Cell::new(registered),
+            lock: Some(lock),
+        };
+        
+        if tracker.json_storage {
+            tracker.load_json()?;
+        } else {
+            tracker.load_data()?;
+            tracker.load_logs()?;
+            tracker.load_nags()?;
+        }
+        tracker.load_config()?;
+        tracker.load_groups()?;
+        if !registered && !tracker.patient_files(dir)?.is_empty() {
+            tracker.register_patient()?;
+        }
+        tracker.refresh_display_order();
+        for name in tracker.orphaned_medications() {
+            tracker.load_warnings.push(format!(
+                "Logs mention {}, which is not in the medication list. Its entries are left out of summaries; use Check & Repair Data to remove them.",
+                name));
+        }
+        Ok(tracker)
+    }
+
+    pub fn switch_patient(&mut self, patient_name: &str) -> io::Result<()> {
+        let patient_name = patient_name.trim();
+        if patient_name.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Patient name cannot be empty"));
+        }
+        
+        self.save_all()?;
+        self.reopen(patient_name)
+    }
+
+    /// Reloads this tracker for `patient_name`, keeping session settings. The current lock is
+    /// released first so the same patient can be reopened, and taken back if loading fails.
+    fn reopen(&mut self, patient_name: &str) -> io::Result<()> {
+        let lock_path = self.lock.take().map(|lock| lock.path.clone());
+        match MedicationTracker::with_data_dir(patient_name, &self.data_dir) {
+            Ok(tracker) => {
+                let (immediate, locale) = (self.immediate_persistence, self.locale);
+                *self = tracker;
+                self.immediate_persistence = immediate;
+                self.locale = locale;
+                Ok(())
+            }
+            Err(e) => {
+                self.lock = lock_path.and_then(|path| DataLock::acquire(path, &self.patient_name).ok());
+                Err(e)
+            }
+        }
+    }
+
+    pub fn copy_regimen_to(&self, new_patient: &str) -> io::Result<MedicationTracker> {
+        let new_patient = new_patient.trim();
+        if new_patient.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Patient name cannot be empty"));
+        }
+        if new_patient == self.patient_name {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot copy a regimen onto the same patient"));
+        }
+        
+        let mut copy = MedicationTracker::with_data_dir(new_patient, &self.data_dir)?;
+        if !copy.medications.is_empty() || !copy.daily_logs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists,
+                format!("{} already has medication data", new_patient)));
+        }
+        
+        for med in self.medications.values() {
+            let mut med = med.clone();
+            med.current_count = med.total_prescribed;
+            med.added_on = get_today();
+            copy.medications.insert(med.name.clone(), med);
+        }
+        copy.refresh_display_order();
+        copy.immediate_persistence = self.immediate_persistence;
+        copy.locale = self.locale;
+        copy.save_data()?;
+        copy.save_all()?;
+        copy.audit(&format!("copy_regimen,{}", escape_csv_field(&self.patient_name)))?;
+        Ok(copy)
+    }
+
+    pub fn patient_name(&self) -> &str {
+        &self.patient_name
+    }
+
+    pub fn medication_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.medications.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn resolve_medication(&self, input: &str) -> Result<String, String> {
+        let query = input.trim().to_lowercase();
+        if query.is_empty() {
+            return Err("Please enter a medication name".to_string());
+        }
+        
+        let names = self.medication_names();
+        if let Some(name) = names.iter().find(|name| name.to_lowercase() == query) {
+            return Ok(name.clone());
+        }
+        
+        let prefixed: Vec<&String> = names.iter()
+            .filter(|name| name.to_lowercase().starts_with(&query))
+            .collect();
+        match prefixed.as_slice() {
+            [name] => return Ok((*name).clone()),
+            [] => {}
+            candidates => {
+                let candidates: Vec<&str> = candidates.iter().map(|name| name.as_str()).collect();
+                return Err(format!("'{}' matches several medications: {}", input.trim(), candidates.join(", ")));
+            }
+        }
+        
+        let close: Vec<&String> = names.iter()
+            .filter(|name| edit_distance(&name.to_lowercase(), &query) <= 2)
+            .collect();
+        match close.as_slice() {
+            [name] => Err(format!("Did you mean '{}'?", name)),
+            _ => Err(format!("No medication matches '{}'", input.trim())),
+        }
+    }
+
+    pub fn get_medication(&self, name: &str) -> Option<&Medication> {
+        self.medications.get(name)
+    }
+
+    pub fn uses_json_storage(&self) -> bool {
+        self.json_storage
+    }
+
+    pub fn json_file(&self) -> &Path {
+        &self.json_file
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn add_medication(&mut self, name: String, dosage: String, time_of_day: String, count: u32, doses_per_day: u32, notes: String) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("Medication name cannot be empty".to_string());
+        }
+        if dosage.trim().is_empty() {
+            return Err("Dosage cannot be empty".to_string());
+        }
+        if dosage.trim().starts_with('-') {
+            return Err("Dosage cannot be negative".to_string());
+        }
+        if self.medications.contains_key(&name) {
+            return Err(format!("{} already exists. Use Refill or Edit to change it.", name));
+        }
+
+        let is_prn = is_as_needed(&time_of_day);
+        let med = Medication {
+            name: name.clone(),
+            parsed_dosage: parse_dosage(&dosage).ok(),
+            interval_start: None,
+            dosage,
+            time_of_day,
+            current_count: Quantity::whole(count),
+            total_prescribed: Quantity::whole(count),
+            doses_per_day: doses_per_day.max(1),
+            refill_threshold: DEFAULT_REFILL_THRESHOLD,
+            notes,
+            added_on: get_today(),
+            max_count: None,
+            priority: Priority::Normal,
+            is_prn,
+            frequency: Frequency::Daily,
+            cost_per_unit: None,
+            grace_minutes: default_grace_minutes(),
+            paused: false,
+            units_per_dose: default_units_per_dose(),
+            refill_size: 0,
+            refills_remaining: 0,
+            start_date: None,
+            end_date: None,
+            pauses: Vec::new(),
+        };
+        self.medications.insert(name.clone(), med);
+        self.refresh_display_order();
+        self.save_data().map_err(|e| e.to_string())?;
+        self.audit(&format!("add_medication,{}", escape_csv_field(&name))).map_err(|e| e.to_string())
+    }
+
+    /// Imports medications from a CSV file whose first line is the header
+    /// `name,dosage,time_of_day,count`. Rows that fail validation are skipped and listed in
+    /// `import_errors`; the number of medications added is returned.
+    pub fn import_medications_csv(&mut self, path: &Path) -> Result<usize, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let mut lines = contents.trim_start_matches('\u{feff}').lines().enumerate()
+            .map(|(i, line)| (i + 1, line.trim_end_matches('\r').trim()))
+            .filter(|(_, line)| !line.is_empty());
+        
+        let header: Vec<String> = match lines.next() {
+            Some((_, line)) => parse_csv_line(line).iter().map(|field| field.trim().to_lowercase()).collect(),
+            None => return Err(format!("{} is empty", path.display())),
+        };
+        if header != ["name", "dosage", "time_of_day", "count"] {
+            return Err("Expected the header: name,dosage,time_of_day,count".to_string());
+        }
+        
+        self.import_errors.clear();
+        let mut imported = 0;
+        for (line_number, line) in lines {
+            let parts = parse_csv_line(line);
+            let result = if parts.len() != 4 {
+                Err(format!("expected 4 fields, found {}", parts.len()))
+            } else {
+                parse_schedule(&parts[2])
+                    .and_then(|schedule| parse_quantity(&parts[3]).map(|count| (schedule, count)))
+                    .and_then(|(schedule, count)| self.add_medication(
+                        parts[0].trim().to_string(),
+                        parts[1].trim().to_string(),
+                        schedule.to_string(),
+                        count,
+                        1,
+                        String::new(),
+                    ))
+            };
+            
+            match result {
+                Ok(()) => imported += 1,
+                Err(e) => self.import_errors.push(format!("Line {}: {}", line_number, e)),
+            }
+        }
+        Ok(imported)
+    }
+
+    pub fn import_errors(&self) -> &[String] {
+        &self.import_errors
+    }
+
+    pub fn mark_taken(&mut self, med_name: &str, date: &str, dose: u32, taken: bool) -> Result<MarkOutcome, String> {
+        let status = if taken { DoseStatus::Taken } else { DoseStatus::Missed };
+        let outcome = self.record_dose(med_name, date, dose, status, None)?;
+        self.audit(&format!("mark_taken,{},{}", escape_csv_field(med_name), taken)).map_err(|e| e.to_string())?;
+        Ok(outcome)
+    }
+
+    /// Marks a dose taken and records who gave it. A blank name or "self" means the patient did.
+    pub fn mark_taken_by(&mut self, med_name: &str, date: &str, dose: u32, administered_by: &str) -> Result<MarkOutcome, String> {
+        let administered_by = administered_by.trim();
+        let administered_by = (!administered_by.is_empty() && !administered_by.eq_ignore_ascii_case("self")).then_some(administered_by);
+        let outcome = self.record_dose(med_name, date, dose, DoseStatus::Taken, administered_by)?;
+        self.audit(&format!("mark_taken,{},true,{}", escape_csv_field(med_name), escape_csv_field(administered_by.unwrap_or("self"))))
+            .map_err(|e| e.to_string())?;
+        Ok(outcome)
+    }
+
+    pub fn skip_dose(&mut self, med_name: &str, date: &str, dose: u32, reason: &str) -> Result<(), String> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err("A reason is required to skip a dose".to_string());
+        }
+        self.record_dose(med_name, date, dose, DoseStatus::Skipped(reason.to_string()), None)?;
+        self.audit(&format!("skip_dose,{},{}", escape_csv_field(med_name), escape_csv_field(reason))).map_err(|e| e.to_string())
+    }
+
+    fn record_dose(&mut self, med_name: &str, date: &str, dose: u32, status: DoseStatus, administered_by: Option<&str>) -> Result<MarkOutcome, String> {
+        let date = &normalize_date(date)?;
+        let doses_per_day = match self.medications.get(med_name) {
+            Some(med) => med.doses_per_day,
+            None => return Err("Medication not found".to_string()),
+        };
+
+        if dose == 0 || dose > doses_per_day {
+            return Err(format!("Dose must be between 1 and {}", doses_per_day));
+        }
+        let today = get_today();
+        if *date > today {
+            return Err(format!("Cannot log a dose for {}, which is in the future", date));
+        }
+
+        let log = self.daily_logs.entry(date.to_string()).or_insert_with(|| DailyLog::new(date));
+        let doses = log.taken.entry(med_name.to_string()).or_default();
+        
+        let taken = status == DoseStatus::Taken;
+        let was_taken = doses.get(&dose).is_some_and(|record| record.is_taken());
+        if taken && was_taken {
+            // Already taken: keep the original time and leave the count alone.
+            return Ok(MarkOutcome::Recorded);
+        }
+        let was_deducted = doses.get(&dose).is_some_and(|record| record.deducted);
+        
+        let mut count_change = 0;
+        let mut amount = Quantity::ZERO;
+        let mut outcome = MarkOutcome::Recorded;
+        if let Some(med) = self.medications.get_mut(med_name) {
+            amount = med.units_per_dose;
+            if taken && med.current_count < amount {
+                outcome = MarkOutcome::OutOfStock;
+            } else if taken {
+                med.current_count = med.current_count.saturating_sub(amount);
+                count_change = -1;
+            } else if was_deducted && med.current_count.saturating_add(amount) <= med.total_prescribed {
+                med.current_count = med.current_count.saturating_add(amount);
+                count_change = 1;
+            }
+        }
+        
+        let record = DoseRecord {
+            status,
+            time: (*date == today).then(|| Local::now().format(TIME_FORMAT).to_string()),
+            administered_by: administered_by.filter(|_| taken).map(str::to_string),
+            deducted: count_change == -1,
+        };
+        let doses = self.daily_logs.entry(date.to_string()).or_insert_with(|| DailyLog::new(date))
+            .taken.entry(med_name.to_string()).or_default();
+        let previous = doses.insert(dose, record);
+        
+        self.history.push(MarkAction {
+            med_name: med_name.to_string(),
+            date: date.to_string(),
+            dose,
+            previous,
+            count_change,
+            amount,
+        });
+        if self.history.len() > MAX_UNDO_HISTORY {
+            self.history.remove(0);
+        }
+        
+        self.save_logs().map_err(|e| e.to_string())?;
+        if count_change != 0 {
+            self.save_data().map_err(|e| e.to_string())?;
+        }
+        Ok(outcome)
+    }
+
+    pub fn mark_all_taken(&mut self, date: &str) -> Result<usize, String> {
+        let date = &normalize_date(date)?;
+        let mut names: Vec<String> = self.medications.values()
+            .filter(|med| !med.is_prn && med.is_due_on(date))
+            .map(|med| med.name.clone())
+            .collect();
+        names.sort();
+        
+        let mut newly_marked = 0;
+        for name in names {
+            while let Ok(dose) = self.next_untaken_dose(&name, date) {
+                self.mark_taken(&name, date, dose, true)?;
+                newly_marked += 1;
+            }
+        }
+        Ok(newly_marked)
+    }
+
+    pub fn next_untaken_dose(&self, med_name: &str, date: &str) -> Result<u32, String> {
+        let date = &normalize_date(date)?;
+        let med = self.medications.get(med_name).ok_or("Medication not found")?;
+        let doses = self.daily_logs.get(date).and_then(|log| log.taken.get(med_name));
+        
+        (1..=med.doses_per_day)
+            .find(|dose| !doses
+                .and_then(|d| d.get(dose))
+                .is_some_and(|record| record.is_taken() || record.is_skipped()))
+            .ok_or_else(|| format!("All doses of {} are already taken or skipped for {}", med_name, date))
+    }
+
+    pub fn relog_dose(&mut self, med_name: &str, from_date: &str, to_date: &str) -> Result<(), String> {
+        let from_date = normalize_date(from_date)?;
+        let to_date = normalize_date(to_date)?;
+        if from_date == to_date {
+            return Err("Source and destination dates are the same".to_string());
+        }
+        
+        let has_entry = self.daily_logs.get(&from_date)
+            .and_then(|log| log.taken.get(med_name))
+            .is_some_and(|doses| !doses.is_empty());
+        if !has_entry {
+            return Err(format!("No logged doses of {} on {}", med_name, from_date));
+        }
+        
+        let clashes = self.daily_logs.get(&to_date)
+            .and_then(|log| log.taken.get(med_name))
+            .is_some_and(|doses| !doses.is_empty());
+        if clashes {
+            return Err(format!("{} already has doses logged on {}", med_name, to_date));
+        }
+        
+        let source = self.daily_logs.get_mut(&from_date).ok_or("Source log not found")?;
+        let doses = source.taken.remove(med_name).unwrap_or_default();
+        if source.is_empty() {
+            self.daily_logs.remove(&from_date);
+        }
+        
+        let destination = self.daily_logs.entry(to_date.clone()).or_insert_with(|| DailyLog::new(&to_date));
+        destination.taken.insert(med_name.to_string(), doses);
+        self.history.retain(|action| action.med_name != med_name);
+        
+        self.save_logs().map_err(|e| e.to_string())?;
+        self.audit(&format!("relog_dose,{},{},{}", escape_csv_field(med_name), from_date, to_date))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn reset_day(&mut self, date: &str) -> Result<u32, String> {
+        let date = normalize_date(date)?;
+        let log = self.daily_logs.remove(&date).ok_or_else(|| format!("No log entries for {}", date))?;
+        
+        let mut cleared = 0;
+        for (med_name, doses) in &log.taken {
+            cleared += doses.len() as u32;
+            let deducted = doses.values().filter(|record| record.is_taken() && record.deducted).count() as u32;
+            if let Some(med) = self.medications.get_mut(med_name) {
+                med.current_count = med.current_count
+                    .saturating_add(med.units_per_dose.saturating_mul(deducted))
+                    .min(med.total_prescribed);
+            }
+        }
+        self.history.retain(|action| action.date != date);
+        
+        self.save_logs().map_err(|e| e.to_string())?;
+        self.save_data().map_err(|e| e.to_string())?;
+        self.audit(&format!("reset_day,{}", date)).map_err(|e| e.to_string())?;
+        Ok(cleared)
+    }
+
+    pub fn undo_last(&mut self) -> Result<String, String> {
+        let action = self.history.pop().ok_or("Nothing to undo")?;
+        
+        let med = match self.medications.get_mut(&action.med_name) {
+            Some(med) => med,
+            None => return Err(format!("Cannot undo: {} is no longer being tracked", action.med_name)),
+        };
+        match action.count_change {
+            -1 => med.current_count = med.current_count.saturating_add(action.amount),
+            1 => med.current_count = med.current_count.saturating_sub(action.amount),
+            _ => {}
+        }
+        
+        if let Some(log) = self.daily_logs.get_mut(&action.date) {
+            if let Some(doses) = log.taken.get_mut(&action.med_name) {
+                match action.previous {
+                    Some(previous) => {
+                        doses.insert(action.dose, previous);
+                    }
+                    None => {
+                        doses.remove(&action.dose);
+                    }
+                }
+                if doses.is_empty() {
+                    log.taken.remove(&action.med_name);
+                }
+            }
+            if log.is_empty() {
+                self.daily_logs.remove(&action.date);
+            }
+        }
+        
+        self.save_logs().map_err(|e| e.to_string())?;
+        self.save_data().map_err(|e| e.to_string())?;
+        Ok(format!("Undid {} dose {} on {}", action.med_name, action.dose, action.date))
+    }
+
+    pub fn today_status(&self, date: &str) -> Result<Vec<MedicationStatus>, String> {
+        let date = &normalize_date(date)?;
+        let mut status = Vec::with_capacity(self.display_order.len());
+        
+        for name in &self.display_order {
+            let med = &self.medications[name];
+            let doses_taken = self.doses_taken(name, date);
+            if !med.is_due_on(date) && doses_taken == 0 {
+                continue;
+            }
+            let taken = if med.is_prn { doses_taken > 0 } else { doses_taken >= med.doses_per_day };
+            let snoozed_until = self.snoozed_until(name, date);
+            
+            status.push(MedicationStatus {
+                name: name.clone(),
+                dosage: med.dosage.clone(),
+                time_of_day: med.time_of_day.clone(),
+                priority: med.priority,
+                is_prn: med.is_prn,
+                doses_taken,
+                doses_per_day: med.doses_per_day,
+                taken,
+                reminder_needed: !med.is_prn && !taken && snoozed_until.is_none(),
+                snoozed_until,
+                last_taken_time: self.last_taken_time(name, date),
+                late: taken && self.taken_late(name, date),
+            });
+        }
+        
+        Ok(status)
+    }
+
+    pub fn check_today_status(&self, date: &str) -> Result<Vec<(String, String, bool, String)>, String> {
+        let date = &normalize_date(date)?;
+        
+        Ok(self.today_status(date)?
+            .into_iter()
+            .map(|entry| {
+                let name = &entry.name;
+                let reminder = if entry.is_prn {
+                    format!("As needed - taken {} time{} today", entry.doses_taken, if entry.doses_taken == 1 { "" } else { "s" })
+                } else if !entry.taken {
+                    let now = Local::now();
+                    let now = if *date == now.format(DATE_FORMAT).to_string() {
+                        clock_minutes(&now.format(CLOCK_FORMAT).to_string())
+                    } else {
+                        None
+                    };
+                    match (&entry.snoozed_until, self.escalated_reminder(name, date, now)) {
+                        (Some(until), _) => format!("Snoozed until {}", until),
+                        (None, Some(escalated)) => escalated,
+                        (None, None) if entry.priority == Priority::High => format!("CRITICAL: Take {} at {}", name, entry.time_of_day),
+                        (None, None) => format!("REMINDER: Take {} at {}", name, entry.time_of_day),
+                    }
+                } else {
+                    let late = if entry.late { " (late)" } else { "" };
+                    match &entry.last_taken_time {
+                        Some(time) => format!("Taken at {}{}", time.get(..5).unwrap_or(time), late),
+                        None => format!("Taken{}", late),
+                    }
+                };
+                
+                let med = &self.medications[name];
+                let details = if entry.doses_per_day > 1 {
+                    format!("{} - {}/{} doses taken", med, entry.doses_taken, entry.doses_per_day)
+                } else {
+                    med.to_string()
+                };
+                
+                (entry.name, details, entry.taken, reminder)
+            })
+            .collect())
+    }
+
+    pub fn daily_digest(&self, date: &str) -> Result<String, String> {
+        let date = normalize_date(date)?;
+        let mut scheduled: Vec<&Medication> = self.medications.values()
+            .filter(|med| !med.is_prn && med.is_due_on(&date))
+            .collect();
+        scheduled.sort_by_key(|med| (schedule_sort_key(&med.time_of_day), med.name.as_str()));
+        
+        let header = format!("{} {}", self.patient_name, date);
+        if scheduled.is_empty() {
+            return Ok(format!("{}: no medications scheduled", header).replace(['\r', '\n'], " "));
+        }
+        
+        let mut missed = Vec::new();
+        let mut skipped = Vec::new();
+        for med in &scheduled {
+            let doses_taken = self.doses_taken(&med.name, &date);
+            if doses_taken >= med.doses_per_day {
+                continue;
+            }
+            
+            let entry = format!("{} ({})", med.name, med.time_of_day);
+            if doses_taken + self.doses_skipped(&med.name, &date).len() as u32 >= med.doses_per_day {
+                skipped.push(entry);
+            } else {
+                missed.push(entry);
+            }
+        }
+        
+        let total = scheduled.len();
+        let taken = total - missed.len() - skipped.len();
+        let mut digest = if taken == total {
+            format!("{}: all {} taken, great job!", header, total)
+        } else {
+            format!("{}: {}/{} taken", header, taken, total)
+        };
+        if !missed.is_empty() {
+            digest.push_str(&format!(", missed {}", missed.join(", ")));
+        }
+        if !skipped.is_empty() {
+            digest.push_str(&format!(", skipped {}", skipped.join(", ")));
+        }
+        Ok(digest.replace(['\r', '\n'], " "))
+    }
+
+    pub fn get_missed_medications(&self, date: &str) -> Result<Vec<String>, String> {
+        Ok(self.missed_on(&normalize_date(date)?))
+    }
+
+    /// Missed doses for an already normalized `date`; today only counts doses past their grace.
+    fn missed_on(&self, date: &str) -> Vec<String> {
+        let now = Local::now();
+        let cutoff = if date == now.format(DATE_FORMAT).to_string() {
+            clock_minutes(&now.format(CLOCK_FORMAT).to_string())
+        } else {
+            None
+        };
+        
+        self.outstanding_medications(date, |scheduled, grace| match (scheduled, cutoff) {
+            (Some(scheduled), Some(cutoff)) => scheduled + grace <= cutoff,
+            _ => true,
+        })
+    }
+
+    pub fn days_with_misses(&self, start_date: &str, end_date: &str) -> Vec<(String, Vec<String>)> {
+        let (start_date, end_date) = match (normalize_date(start_date), normalize_date(end_date)) {
+            (Ok(start), Ok(end)) => (start, end),
+            _ => return Vec::new(),
+        };
+        
+        let mut names = self.medication_names();
+        names.retain(|name| !self.medications[name].is_prn);
+        
+        let mut bad_days = Vec::new();
+        for date in date_range(&start_date, &end_date) {
+            let missed: Vec<String> = names.iter()
+                .filter(|name| {
+                    let med = &self.medications[*name];
+                    let scheduled = (med.added_on.is_empty() || med.added_on <= date) && med.is_due_on(&date);
+                    let accounted = self.doses_taken(name, &date) + self.doses_skipped(name, &date).len() as u32;
+                    scheduled && accounted < med.doses_per_day
+                })
+                .cloned()
+                .collect();
+            
+            if !missed.is_empty() {
+                bad_days.push((date, missed));
+            }
+        }
+        bad_days
+    }
+
+    pub fn due_medications(&self, date: &str, now: &str) -> Vec<String> {
+        let now = clock_minutes(now);
+        self.outstanding_medications(date, |scheduled, grace| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled <= now && now < scheduled + grace,
+            _ => false,
+        })
+    }
+
+    pub fn overdue_medications(&self, date: &str, now: &str) -> Vec<String> {
+        let now = clock_minutes(now);
+        self.outstanding_names(date, |scheduled, grace| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled + grace <= now,
+            _ => true,
+        })
+        .into_iter()
+        .map(|name| self.escalated_reminder(name, date, now).unwrap_or_else(|| self.outstanding_label(name, date)))
+        .collect()
+    }
+
+    pub fn upcoming_medications(&self, date: &str, now: &str) -> Vec<String> {
+        let now = clock_minutes(now);
+        self.outstanding_medications(date, |scheduled, _| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled > now,
+            _ => false,
+        })
+    }
+
+    /// Outstanding doses scheduled after `now_time` but no more than `window_minutes` later. Bucket
+    /// names count at their representative clock time. Only `date` is considered, so a window that
+    /// runs past midnight does not pick up the next day's doses.
+    pub fn due_soon(&self, date: &str, now_time: &str, window_minutes: u32) -> Vec<String> {
+        let now = clock_minutes(now_time);
+        self.outstanding_medications(date, |scheduled, _| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled > now && scheduled <= now + window_minutes,
+            _ => false,
+        })
+    }
+
+    pub fn next_dose(&self, date: &str, now_time: &str) -> Option<(String, String)> {
+        let now = clock_minutes(now_time)?;
+        self.outstanding_names(date, |scheduled, _| scheduled.is_some_and(|scheduled| scheduled > now))
+            .into_iter()
+            .filter_map(|name| Some((clock_minutes(&self.medications[name].time_of_day)?, name)))
+            .min()
+            .map(|(scheduled, name)| (name.to_string(), format!("{:02}:{:02}", scheduled / 60, scheduled % 60)))
+    }
+
+    /// Due and overdue reminders, leaving out medications whose time-of-day window doesn't cover
+    /// `now_time` (so Bedtime doses stay quiet in the morning and vice versa).
+    pub fn active_reminders(&self, date: &str, now_time: &str) -> Vec<String> {
+        self.active_reminder_entries(date, now_time)
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    /// Same as `active_reminders`, paired with the medication each message is about.
+    pub fn active_reminder_entries(&self, date: &str, now_time: &str) -> Vec<(String, String)> {
+        let now = clock_minutes(now_time);
+        self.outstanding_names(date, |scheduled, _| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled <= now,
+            _ => true,
+        })
+        .into_iter()
+        .filter(|name| now.is_none_or(|now| in_reminder_window(&self.medications[*name].time_of_day, now)))
+        .map(|name| {
+            let med = &self.medications[name];
+            let past_grace = match (clock_minutes(&med.time_of_day), now) {
+                (Some(scheduled), Some(now)) => scheduled + med.grace_minutes <= now,
+                _ => true,
+            };
+            let escalated = if past_grace { self.escalated_reminder(name, date, now) } else { None };
+            (name.to_string(), escalated.unwrap_or_else(|| self.outstanding_label(name, date)))
+        })
+        .collect()
+    }
+
+    /// Sends every active reminder to `sink` and returns how many were sent.
+    pub fn notify_reminders(&self, date: &str, now_time: &str, sink: &dyn ReminderSink) -> usize {
+        let reminders = self.group_reminder_entries(self.active_reminder_entries(date, now_time));
+        for (med, message) in &reminders {
+            sink.notify(med, message);
+        }
+        reminders.len()
+    }
+
+    fn outstanding_medications(&self, date: &str, include: impl Fn(Option<u32>, u32) -> bool) -> Vec<String> {
+        self.outstanding_names(date, include)
+            .into_iter()
+            .map(|name| self.outstanding_label(name, date))
+            .collect()
+    }
+
+    fn outstanding_names(&self, date: &str, include: impl Fn(Option<u32>, u32) -> bool) -> Vec<&str> {
+        let mut outstanding = Vec::new();
+        for name in &self.display_order {
+            let med = &self.medications[name];
+            if !self.is_outstanding(med, date) || self.snoozed_until(name, date).is_some() {
+                continue;
+            }
+            if !include(clock_minutes(&med.time_of_day), med.grace_minutes) {
+                continue;
+            }
+            outstanding.push(name.as_str());
+        }
+        
+        outstanding
+    }
+
+    fn is_outstanding(&self, med: &Medication, date: &str) -> bool {
+        let accounted = self.doses_taken(&med.name, date) + self.doses_skipped(&med.name, date).len() as u32;
+        !med.is_prn && med.is_due_on(date) && accounted < med.doses_per_day
+    }
+
+    fn outstanding_label(&self, name: &str, date: &str) -> String {
+        let med = &self.medications[name];
+        let prefix = if med.priority == Priority::High { tr(self.locale, "CRITICAL: ") } else { "" };
+        let label = tr(self.locale, "{med} at {time}")
+            .replace("{med}", name)
+            .replace("{time}", &localize_time_of_day(self.locale, &med.time_of_day));
+        if med.doses_per_day > 1 {
+            let progress = tr(self.locale, "{taken}/{total} doses taken")
+                .replace("{taken}", &self.doses_taken(name, date).to_string())
+                .replace("{total}", &med.doses_per_day.to_string());
+            format!("{}{} ({})", prefix, label, progress)
+        } else {
+            format!("{}{}", prefix, label)
+        }
+    }
+
+    /// 0 when nothing is outstanding, otherwise 1-3 depending on how many times the reminder has
+    /// been shown without the dose being taken or the reminder being dismissed.
+    pub fn reminder_level(&self, med_name: &str, date: &str) -> u8 {
+        let Some(med) = self.medications.get(med_name) else {
+            return 0;
+        };
+        if !self.is_outstanding(med, date) {
+            return 0;
+        }
+        
+        let shown = self.daily_logs.get(date)
+            .and_then(|log| log.reminders_shown.get(med_name))
+            .copied()
+            .unwrap_or(0);
+        shown.clamp(1, 3) as u8
+    }
+
+    pub fn record_reminders_shown(&mut self, date: &str, now: &str) -> io::Result<()> {
+        let now = clock_minutes(now);
+        let names: Vec<String> = self.outstanding_names(date, |scheduled, _| match (scheduled, now) {
+            (Some(scheduled), Some(now)) => scheduled <= now,
+            _ => true,
+        })
+        .into_iter()
+        .filter(|name| now.is_none_or(|now| in_reminder_window(&self.medications[*name].time_of_day, now)))
+        .map(str::to_string)
+        .collect();
+        if names.is_empty() {
+            return Ok(());
+        }
+        
+        let log = self.daily_logs.entry(date.to_string()).or_insert_with(|| DailyLog::new(date));
+        for name in names {
+            if !log.acknowledged.contains(&name) {
+                *log.reminders_shown.entry(name).or_insert(0) += 1;
+            }
+        }
+        self.save_logs()
+    }
+
+    pub fn acknowledge_reminder(&mut self, med_name: &str, date: &str) -> Result<(), String> {
+        let date = &normalize_date(date)?;
+        if !self.medications.contains_key(med_name) {
+            return Err("Medication not found".to_string());
+        }
+        
+        let log = self.daily_logs.entry(date.to_string()).or_insert_with(|| DailyLog::new(date));
+        log.acknowledged.insert(med_name.to_string());
+        self.save_logs().map_err(|e| e.to_string())
+    }
+
+    fn escalated_reminder(&self, med_name: &str, date: &str, now: Option<u32>) -> Option<String> {
+        let med = self.medications.get(med_name)?;
+        let template = match self.reminder_level(med_name, date) {
+            2 => "IMPORTANT: {med} still not taken (due {time})",
+            3 => match (clock_minutes(&med.time_of_day), now) {
+                (Some(scheduled), Some(now)) if now >= scheduled + 60 => {
+                    let hours = (now - scheduled) / 60;
+                    let template = if hours == 1 { "URGENT: {med} overdue {hours} hour" } else { "URGENT: {med} overdue {hours} hours" };
+                    return Some(tr(self.locale, template).replace("{med}", med_name).replace("{hours}", &hours.to_string()));
+                }
+                _ => "URGENT: {med} still not taken (due {time})",
+            },
+            _ => return None,
+        };
+        Some(tr(self.locale, template)
+            .replace("{med}", med_name)
+            .replace("{time}", &localize_time_of_day(self.locale, &med.time_of_day)))
+    }
+
+    pub fn snooze_reminder(&mut self, med_name: &str, date: &str, until: &str) -> Result<(), String> {
+        let date = &normalize_date(date)?;
+        if !self.medications.contains_key(med_name) {
+            return Err("Medication not found".to_string());
+        }
+        
+        let until = match parse_schedule(until)? {
+            Schedule::Clock(hour, minute) => format!("{:02}:{:02}", hour, minute),
+            Schedule::Named(_) => return Err("Snooze time must be a clock time (HH:MM)".to_string()),
+        };
+        
+        let log = self.daily_logs.entry(date.to_string()).or_insert_with(|| DailyLog::new(date));
+        log.snoozed_until.insert(med_name.to_string(), until);
+        
+        self.save_logs().map_err(|e| e.to_string())
+    }
+
+    fn snoozed_until(&self, med_name: &str, date: &str) -> Option<String> {
+        let until = self.daily_logs.get(date)?.snoozed_until.get(med_name)?;
+        let now = Local::now();
+        
+        let active = date == now.format(DATE_FORMAT).to_string()
+            && now.format(CLOCK_FORMAT).to_string() < *until;
+        if active {
+            Some(until.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn doses_taken(&self, med_name: &str, date: &str) -> u32 {
+        self.daily_logs
+            .get(date)
+            .and_then(|log| log.taken.get(med_name))
+            .map(|doses| doses.values().filter(|record| record.is_taken()).count() as u32)
+            .unwrap_or(0)
+    }
+
+    fn administered_by_others(&self, med_name: &str, date: &str) -> Vec<String> {
+        let Some(doses) = self.daily_logs.get(date).and_then(|log| log.taken.get(med_name)) else {
+            return Vec::new();
+        };
+        let mut names: Vec<(u32, String)> = doses.iter()
+            .filter_map(|(dose, record)| Some((*dose, record.administered_by.clone()?)))
+            .collect();
+        names.sort();
+        names.into_iter().map(|(_, name)| name).collect()
+    }
+
+    pub fn doses_skipped(&self, med_name: &str, date: &str) -> Vec<String> {
+        let mut reasons: Vec<(u32, String)> = self.daily_logs
+            .get(date)
+            .and_then(|log| log.taken.get(med_name))
+            .map(|doses| doses.iter()
+                .filter_map(|(dose, record)| match &record.status {
+                    DoseStatus::Skipped(reason) => Some((*dose, reason.clone())),
+                    _ => None,
+                })
+                .collect())
+            .unwrap_or_default();
+        reasons.sort();
+        reasons.into_iter().map(|(_, reason)| reason).collect()
+    }
+
+    fn last_taken_time(&self, med_name: &str, date: &str) -> Option<String> {
+        self.daily_logs
+            .get(date)
+            .and_then(|log| log.taken.get(med_name))
+            .and_then(|doses| doses.values()
+                .filter(|record| record.is_taken())
+                .filter_map(|record| record.time.clone())
+                .max())
+    }
+
+    /// A dose counts as late when the first dose of the day was logged more than its grace period
+    /// after its scheduled clock time. Later doses of multi-dose meds have no scheduled time to compare against.
+    pub fn taken_late(&self, med_name: &str, date: &str) -> bool {
+        let (scheduled, grace) = match self.medications.get(med_name) {
+            Some(med) if !med.is_prn => (clock_minutes(&med.time_of_day), med.grace_minutes),
+            _ => (None, 0),
+        };
+        let Some(scheduled) = scheduled else {
+            return false;
+        };
+        
+        self.daily_logs
+            .get(date)
+            .and_then(|log| log.taken.get(med_name))
+            .and_then(|doses| doses.get(&1))
+            .filter(|record| record.is_taken())
+            .and_then(|record| record.time.as_deref())
+            .and_then(|time| clock_minutes(time.get(..5)?))
+            .is_some_and(|taken_at| taken_at > scheduled + grace)
+    }
+
+    /// The clock time (to the nearest 5 minutes) the first dose is usually taken, once there are
+    /// enough timed doses and it differs noticeably from the schedule.
+    pub fn suggested_time(&self, med_name: &str) -> Option<String> {
+        let med = self.medications.get(med_name).filter(|med| !med.is_prn)?;
+        let samples: Vec<u32> = self.daily_logs.values()
+            .filter_map(|log| log.taken.get(med_name)?.get(&1))
+            .filter(|record| record.is_taken())
+            .filter_map(|record| clock_minutes(record.time.as_deref()?.get(..5)?))
+            .collect();
+        if samples.len() < MIN_TIME_SAMPLES {
+            return None;
+        }
+        
+        // Average on the clock face so 23:50 and 00:10 come out as midnight, not noon.
+        const DAY_MINUTES: f32 = 24.0 * 60.0;
+        let (sin, cos) = samples.iter()
+            .map(|&minutes| minutes as f32 / DAY_MINUTES * std::f32::consts::TAU)
+            .fold((0.0, 0.0), |(sin, cos), angle| (sin + angle.sin(), cos + angle.cos()));
+        let average = (sin.atan2(cos) / std::f32::consts::TAU * DAY_MINUTES).rem_euclid(DAY_MINUTES);
+        let suggested = ((average / 5.0).round() as u32 * 5) % (24 * 60);
+        if clock_minutes(&med.time_of_day).is_some_and(|scheduled| {
+            let shift = scheduled.abs_diff(suggested);
+            shift.min(24 * 60 - shift) < MIN_SCHEDULE_SHIFT_MINUTES
+        }) {
+            return None;
+        }
+        Some(format!("{:02}:{:02}", suggested / 60, suggested % 60))
+    }
+
+    /// `None` when nothing was due that day (no scheduled meds yet, paused, or outside a course).
+    fn all_taken_on(&self, date: &str) -> Option<bool> {
+        let due: Vec<&Medication> = self.medications.values()
+            .filter(|med| !med.is_prn && med.is_due_on(date))
+            .filter(|med| med.added_on.is_empty() || med.added_on.as_str() <= date)
+            .collect();
+        
+        if due.is_empty() {
+            return None;
+        }
+        Some(due.iter().all(|med| self.doses_taken(&med.name, date) >= med.doses_per_day))
+    }
+
+    /// Consecutive days with every due dose taken, ending today (or yesterday while today is still
+    /// in progress). Days with nothing due neither extend nor break the streak.
+    pub fn current_streak(&self, today: &str) -> Result<u32, String> {
+        let mut date = normalize_date(today)?;
+        let Some(earliest) = self.daily_logs.keys().min() else {
+            return Ok(0);
+        };
+        if self.all_taken_on(&date) == Some(false) {
+            let Some(yesterday) = add_days(&date, -1) else {
+                return Ok(0);
+            };
+            date = yesterday;
+        }
+        
+        let mut streak = 0;
+        while date.as_str() >= earliest.as_str() {
+            match self.all_taken_on(&date) {
+                Some(true) => streak += 1,
+                Some(false) => break,
+                None => {}
+            }
+            let Some(previous) = add_days(&date, -1) else {
+                break;
+            };
+            date = previous;
+        }
+        Ok(streak)
+    }
+
+    pub fn adherence_rate(&self, med_name: &str, start_date: &str, end_date: &str) -> Result<f32, String> {
+        let start_date = &normalize_date(start_date)?;
+        let end_date = &normalize_date(end_date)?;
+        let med = self.medications.get(med_name).ok_or("Medication not found")?;
+        if med.is_prn {
+            return Err(format!("{} is taken as needed, so it has no adherence rate", med_name));
+        }
+        
+        let mut scheduled = 0;
+        let mut taken = 0;
+        for date in date_range(start_date, end_date) {
+            if !med.added_on.is_empty() && date < med.added_on || !med.is_due_on(&date) {
+                continue;
+            }
+            scheduled += med.doses_per_day;
+            taken += self.doses_taken(med_name, &date).min(med.doses_per_day);
+        }
+        
+        if scheduled == 0 {
+            return Ok(0.0);
+        }
+        Ok((taken as f32 / scheduled as f32) * 100.0)
+    }
+
+    /// Scheduled medications ordered from best to worst adherence over the range, ties broken by name.
+    pub fn adherence_ranking(&self, start: &str, end: &str) -> Vec<(String, f32)> {
+        let mut ranking: Vec<(String, f32)> = self.medications.values()
+            .filter(|med| !med.is_prn && !med.paused)
+            .filter_map(|med| Some((med.name.clone(), self.adherence_rate(&med.name, start, end).ok()?)))
+            .collect();
+        ranking.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranking
+    }
+
+    pub fn adherence_by_time_of_day(&self, start: &str, end: &str) -> HashMap<String, f32> {
+        let (start, end) = match (normalize_date(start), normalize_date(end)) {
+            (Ok(start), Ok(end)) => (start, end),
+            _ => return HashMap::new(),
+        };
+        
+        let mut totals: HashMap<String, (u32, u32)> = HashMap::new();
+        for date in date_range(&start, &end) {
+            for med in self.medications.values() {
+                if med.is_prn || !med.added_on.is_empty() && date < med.added_on || !med.is_due_on(&date) {
+                    continue;
+                }
+                let Some(bucket) = time_bucket(&med.time_of_day) else {
+                    continue;
+                };
+                let entry = totals.entry(bucket.to_string()).or_default();
+                entry.0 += self.doses_taken(&med.name, &date).min(med.doses_per_day);
+                entry.1 += med.doses_per_day;
+            }
+        }
+        
+        totals.into_iter()
+            .filter(|(_, (_, scheduled))| *scheduled > 0)
+            .map(|(bucket, (taken, scheduled))| (bucket, (taken as f32 / scheduled as f32) * 100.0))
+            .collect()
+    }
+
+    /// Counts (taken, missed) doses per tracked medication across every logged day, leaving out
+    /// days outside its course. Log entries for removed medications are not counted.
+    pub fn lifetime_stats(&self) -> HashMap<String, (u32, u32)> {
+        let mut stats: HashMap<String, (u32, u32)> = HashMap::new();
+        
+        for log in self.daily_logs.values() {
+            let in_course = |name: &String| self.medications.get(name).is_some_and(|med| med.in_course(&log.date));
+            for (med_name, doses) in log.taken.iter().filter(|(name, _)| in_course(name)) {
+                let entry = stats.entry(med_name.clone()).or_default();
+                for record in doses.values() {
+                    match record.status {
+                        DoseStatus::Taken => entry.0 += 1,
+                        DoseStatus::Missed => entry.1 += 1,
+                        DoseStatus::Skipped(_) => {}
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn generate_weekly_summary(&self, week_start: &str) -> Result<String, String> {
+        let week_start = &self.week_start(&normalize_date(week_start)?);
+        let mut summary = String::new();
+        summary.push_str(&format!("\n========== {} {} ==========\n", tr(self.locale, "WEEKLY SUMMARY FOR"), self.patient_name));
+        summary.push_str(&format!("{} {}\n\n", tr(self.locale, "Week starting:"), week_start));
+        
+        if self.medications.is_empty() {
+            summary.push_str(tr(self.locale, "No medications on record for this patient."));
+            summary.push('\n');
+            return Ok(summary);
+        }
+
+        let days = self.week_days(week_start);
+        
+        for (med_name, med) in &self.medications {
+            summary.push_str(&format!("{} {} ({})\n", tr(self.locale, "MEDICATION:"), med_name, med.dosage));
+            summary.push_str(&format!("{} ", tr(self.locale, "Daily Record:")));
+            
+            if med.is_prn {
+                let mut times_taken = 0;
+                for (day, date) in &days {
+                    let doses_taken = self.doses_taken(med_name, date);
+                    summary.push_str(&format!("{} {} ", day, doses_taken));
+                    times_taken += doses_taken;
+                }
+                summary.push_str(&format!("\nAs needed: taken {} times this week\n", times_taken));
+                summary.push_str(&format!("Remaining: {} of {} doses\n\n", med.current_count, med.total_prescribed));
+                continue;
+            }
+            
+            let mut taken_count = 0;
+            let mut on_time_count = 0;
+            let mut due_count = 0;
+            let mut skipped = Vec::new();
+            let mut given_by_others = Vec::new();
+            for (day, date) in &days {
+                let doses_taken = self.doses_taken(med_name, date);
+                let reasons = self.doses_skipped(med_name, date);
+                let taken = doses_taken >= med.doses_per_day;
+                let late = taken && self.taken_late(med_name, date);
+                let due = med.is_due_on(date);
+                if due {
+                    due_count += 1;
+                }
+                
+                let symbol = if !due && !taken {
+                    "[-]"
+                } else if late {
+                    "[L]"
+                } else if taken {
+                    "[X]"
+                } else if !reasons.is_empty() && doses_taken + reasons.len() as u32 >= med.doses_per_day {
+                    "[S]"
+                } else {
+                    "[ ]"
+                };
+                summary.push_str(&format!("{} {} ", day, symbol));
+                
+                if taken && due {
+                    taken_count += 1;
+                    if !late {
+                        on_time_count += 1;
+                    }
+                }
+                for reason in reasons {
+                    skipped.push(format!("{} ({})", day, reason));
+                }
+                for by in self.administered_by_others(med_name, date) {
+                    given_by_others.push(format!("{} ({})", day, by));
+                }
+            }
+            
+            let percentage = if due_count == 0 { 0.0 } else { (taken_count as f32 / due_count as f32) * 100.0 };
+            if med.frequency != Frequency::Daily {
+                summary.push_str(&format!("\nFrequency: {}", med.frequency));
+            }
+            summary.push_str(&format!("\nAdherence: {}/{} days ({:.1}%)\n", taken_count, due_count, percentage));
+            summary.push_str(&format!("Chart: {}\n", adherence_bar(percentage)));
+            let on_time = if due_count == 0 { 0.0 } else { (on_time_count as f32 / due_count as f32) * 100.0 };
+            summary.push_str(&format!("On time: {}/{} days ({:.1}%)\n", on_time_count, due_count, on_time));
+            if !skipped.is_empty() {
+                summary.push_str(&format!("Skipped: {}\n", skipped.join(", ")));
+            }
+            if !given_by_others.is_empty() {
+                summary.push_str(&format!("Given by caregiver: {}\n", given_by_others.join(", ")));
+            }
+            summary.push_str(&format!("Remaining: {} of {} doses\n\n", med.current_count, med.total_prescribed));
+        }
+
+        summary.push_str(&format!("{}\n", tr(self.locale, "DAILY OVERVIEW:")));
+        let today = get_today();
+        let mut not_recorded = Vec::new();
+        for (day, date) in &days {
+            let total_meds = self.medications.values().filter(|med| !med.is_prn && med.is_due_on(date)).count();
+            if total_meds > 0 && *date <= today && !self.is_recorded(date) {
+                summary.push_str(&format!("{}: {}\n", day, tr(self.locale, "nothing recorded yet")));
+                not_recorded.push(day.as_str());
+                continue;
+            }
+            let taken_meds = self.medications.values()
+                .filter(|med| !med.is_prn && med.is_due_on(date) && self.doses_taken(&med.name, date) >= med.doses_per_day)
+                .count();
+            
+            summary.push_str(&format!("{}: {}/{} medications taken", day, taken_meds, total_meds));
+            
+            if taken_meds < total_meds {
+                let missed = self.missed_on(date);
+                if !missed.is_empty() {
+                    summary.push_str(&format!(" - MISSED: {}", missed.join(", ")));
+                }
+            }
+            summary.push('\n');
+        }
+        if !not_recorded.is_empty() {
+            summary.push_str(&format!("\n{} {}\n", tr(self.locale, "NOT YET RECORDED:"), not_recorded.join(", ")));
+        }
+
+        summary.push_str("\n==========================================\n");
+        Ok(summary)
+    }
+
+    /// Days in the range where something was scheduled but nothing has been recorded yet.
+    pub fn unlogged_dates(&self, since: &str, until: &str) -> Vec<String> {
+        let (since, until) = match (normalize_date(since), normalize_date(until)) {
+            (Ok(since), Ok(until)) => (since, until),
+            _ => return Vec::new(),
+        };
+        
+        date_range(&since, &until)
+            .into_iter()
+            .filter(|date| !self.scheduled_on(date).is_empty() && !self.is_recorded(date))
+            .collect()
+    }
+
+    /// Scheduled (non-PRN) medications due on `date`, in display order, skipping any added later.
+    pub fn scheduled_on(&self, date: &str) -> Vec<String> {
+        self.display_order.iter()
+            .filter(|name| {
+                let med = &self.medications[*name];
+                !med.is_prn && med.is_due_on(date) && (med.added_on.is_empty() || med.added_on.as_str() <= date)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Whether any dose (taken, missed or skipped) of a tracked medication has been entered for
+    /// `date`. Reminder and snooze bookkeeping alone doesn't count.
+    pub fn is_recorded(&self, date: &str) -> bool {
+        self.daily_logs.get(date).is_some_and(|log| log.taken.keys().any(|name| self.medications.contains_key(name)))
+    }
+
+    /// Scheduled medications with nothing recorded in the last `days` days, unless added within them.
+    pub fn stale_medications(&self, today: &str, days: u32) -> Vec<String> {
+        let Ok(today) = normalize_date(today) else {
+            return Vec::new();
+        };
+        let Some(first) = add_days(&today, 1 - days as i64).filter(|_| days > 0) else {
+            return Vec::new();
+        };
+        let window = date_range(&first, &today);
+        
+        self.display_order.iter()
+            .filter(|name| {
+                let med = &self.medications[*name];
+                !med.is_prn && !med.paused
+                    && (med.added_on.is_empty() || med.added_on <= window[0])
+                    && window.iter().any(|date| med.is_due_on(date))
+                    && !window.iter().any(|date| self.daily_logs.get(date).is_some_and(|log| log.taken.contains_key(*name)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn adherence_trend(&self, num_weeks: u32, ending_week_start: &str) -> Vec<(String, f32)> {
+        let mut trend = Vec::new();
+        
+        for weeks_back in (0..num_weeks as i64).rev() {
+            let Some(week_start) = add_days(ending_week_start, -7 * weeks_back) else {
+                continue;
+            };
+            
+            let scheduled: Vec<&Medication> = self.medications.values().filter(|med| !med.is_prn).collect();
+            let mut taken_days = 0;
+            let mut scheduled_days = 0;
+            for med in &scheduled {
+                for date in (0..7).filter_map(|i| add_days(&week_start, i)) {
+                    if !med.is_due_on(&date) {
+                        continue;
+                    }
+                    scheduled_days += 1;
+                    if self.doses_taken(&med.name, &date) >= med.doses_per_day {
+                        taken_days += 1;
+                    }
+                }
+            }
+            
+            let percentage = if scheduled_days == 0 {
+                0.0
+            } else {
+                (taken_days as f32 / scheduled_days as f32) * 100.0
+            };
+            trend.push((week_start, percentage));
+        }
+        trend
+    }
+
+    pub fn week_has_logs(&self, week_start: &str) -> bool {
+        (0..7).filter_map(|i| add_days(week_start, i)).any(|date| self.daily_logs.contains_key(&date))
+    }
+
+    pub fn weekly_report_path(&self, week_start: &str) -> Result<PathBuf, String> {
+        let week_start = normalize_date(week_start)?;
+        Ok(self.data_dir.join(format!("{}_weekly_report_{}.txt", self.file_stem, week_start)))
+    }
+
+    pub fn save_chart_to_file(&self, week_start: &str, overwrite: bool) -> Result<String, String> {
+        let filename = self.weekly_report_path(week_start)?;
+        if filename.exists() && !overwrite {
+            return Err(format!("{} already exists", filename.display()));
+        }
+        let summary = self.generate_weekly_summary(week_start)?;
+        
+        let mut file = File::create(&filename).map_err(|e| e.to_string())?;
+        file.write_all(summary.as_bytes()).map_err(|e| e.to_string())?;
+        
+        Ok(filename.display().to_string())
+    }
+
+    pub fn generate_monthly_summary(&self, year: i32, month: u32) -> String {
+        let first = match NaiveDate::from_ymd_opt(year, month, 1) {
+            Some(first) => first,
+            None => return format!("Invalid month: {}-{}\n", year, month),
+        };
+        let first_date = first.format(DATE_FORMAT).to_string();
+        let days_in_month = days_in_month(year, month);
+        let dates: Vec<String> = (0..days_in_month).filter_map(|day| add_days(&first_date, day as i64)).collect();
+        let total_meds = self.medications.len();
+
+        let mut summary = String::new();
+        summary.push_str(&format!("\n========== MONTHLY SUMMARY FOR {} ==========\n", self.patient_name));
+        summary.push_str(&format!("Month: {}\n\n", first.format("%B %Y")));
+
+        summary.push_str("CALENDAR (day: medications taken/total):\n");
+        let mut weekday = self.week_start_day;
+        for _ in 0..7 {
+            summary.push_str(&format!("{:<10}", weekday.to_string()));
+            weekday = weekday.succ();
+        }
+        summary.push('\n');
+
+        let offset = (first.weekday().num_days_from_monday() + 7 - self.week_start_day.num_days_from_monday()) % 7;
+        for _ in 0..offset {
+            summary.push_str(&" ".repeat(10));
+        }
+        for (day, date) in (1..).zip(&dates) {
+            let taken_meds = self.medications.values()
+                .filter(|med| self.doses_taken(&med.name, date) >= med.doses_per_day)
+                .count();
+
+            summary.push_str(&format!("{:<10}", format!("{:>2}: {}/{}", day, taken_meds, total_meds)));
+            if (offset + day).is_multiple_of(7) {
+                summary.push('\n');
+            }
+        }
+        if !(offset + days_in_month).is_multiple_of(7) {
+            summary.push('\n');
+        }
+
+        summary.push_str("\nMONTHLY ADHERENCE:\n");
+        for (med_name, med) in &self.medications {
+            let due_dates: Vec<&String> = dates.iter()
+                .filter(|date| med.is_due_on(date))
+                .collect();
+            let taken_days = due_dates.iter()
+                .filter(|date| self.doses_taken(med_name, date) >= med.doses_per_day)
+                .count();
+
+            let percentage = if due_dates.is_empty() { 0.0 } else { (taken_days as f32 / due_dates.len() as f32) * 100.0 };
+            summary.push_str(&format!("{} ({}): {}/{} days ({:.1}%)\n",
+                med_name, med.dosage, taken_days, due_dates.len(), percentage));
+        }
+
+        summary.push_str("\n==========================================\n");
+        summary
+    }
+
+    pub fn save_monthly_report_to_file(&self, year: i32, month: u32) -> Result<String, String> {
+        let summary = self.generate_monthly_summary(year, month);
+        let filename = self.data_dir.join(format!("{}_monthly_report_{}-{:02}.txt", self.file_stem, year, month));
+        
+        let mut file = File::create(&filename).map_err(|e| e.to_string())?;
+        file.write_all(summary.as_bytes()).map_err(|e| e.to_string())?;
+        
+        Ok(filename.display().to_string())
+    }
+
+    /// A plain report for a doctor: per-medication adherence, runs of missed days and current supply.
+    pub fn generate_clinical_report(&self, start: &str, end: &str) -> String {
+        let (start, end) = match (normalize_date(start), normalize_date(end)) {
+            (Ok(start), Ok(end)) if start <= end => (start, end),
+            _ => return format!("Invalid reporting period: {} to {}\n", start, end),
+        };
+        let names = self.medication_names();
+        
+        let mut report = String::new();
+        report.push_str("MEDICATION ADHERENCE REPORT\n");
+        report.push_str(&format!("Patient: {}\n", self.patient_name));
+        report.push_str(&format!("Reporting period: {} to {}\n", start, end));
+        report.push_str(&format!("Generated: {}\n", get_today()));
+        
+        report.push_str("\nAdherence\n");
+        for name in &names {
+            let med = &self.medications[name];
+            match self.adherence_rate(name, &start, &end) {
+                Ok(rate) => report.push_str(&format!("  {} {}: {:.1}%{}\n", name, med.dosage, rate, if med.paused { " (paused)" } else { "" })),
+                Err(_) => {
+                    let taken: u32 = date_range(&start, &end).iter().map(|date| self.doses_taken(name, date)).sum();
+                    report.push_str(&format!("  {} {}: as needed, {} dose{} taken\n", name, med.dosage, taken, if taken == 1 { "" } else { "s" }));
+                }
+            }
+        }
+        
+        report.push_str("\nMissed-dose clusters (2+ days in a row)\n");
+        let bad_days = self.days_with_misses(&start, &end);
+        let mut clusters = 0;
+        for name in &names {
+            let missed: Vec<&str> = bad_days.iter()
+                .filter(|(_, missed)| missed.contains(name))
+                .map(|(date, _)| date.as_str())
+                .collect();
+            
+            let mut i = 0;
+            while i < missed.len() {
+                let mut j = i;
+                while j + 1 < missed.len() && add_days(missed[j], 1).as_deref() == Some(missed[j + 1]) {
+                    j += 1;
+                }
+                if j > i {
+                    report.push_str(&format!("  {}: {} to {} ({} days)\n", name, missed[i], missed[j], j - i + 1));
+                    clusters += 1;
+                }
+                i = j + 1;
+            }
+        }
+        if clusters == 0 {
+            report.push_str("  None\n");
+        }
+        
+        report.push_str("\nCurrent supply\n");
+        for name in &names {
+            let med = &self.medications[name];
+            let mut line = format!("  {}: {} left", name, med.current_count);
+            if let Ok(days) = self.days_until_empty(name) {
+                line.push_str(&format!(", about {} day{}", days, if days == 1 { "" } else { "s" }));
+            }
+            if let Ok(refills) = self.refills_left(name) {
+                line.push_str(&format!(", {} refill{} remaining", refills, if refills == 1 { "" } else { "s" }));
+            }
+            report.push_str(&line);
+            report.push('\n');
+        }
+        report
+    }
+
+    pub fn save_clinical_report_to_file(&self, start: &str, end: &str) -> Result<String, String> {
+        let start = normalize_date(start)?;
+        let end = normalize_date(end)?;
+        let report = self.generate_clinical_report(&start, &end);
+        let filename = self.data_dir.join(format!("{}_clinical_report_{}_{}.txt", self.file_stem, start, end));
+        
+        let mut file = File::create(&filename).map_err(|e| e.to_string())?;
+        file.write_all(report.as_bytes()).map_err(|e| e.to_string())?;
+        
+        Ok(filename.display().to_string())
+    }
+
+    pub fn export_weekly_csv(&self, week_start: &str) -> Result<String, String> {
+        let week_start = &self.week_start(&normalize_date(week_start)?);
+        let days = self.week_days(week_start);
+        let labels: Vec<&str> = days.iter().map(|(label, _)| label.as_str()).collect();
+        let mut csv = format!("Medication,{},Adherence%,Remaining\n", labels.join(","));
+
+        let mut names: Vec<&String> = self.medications.keys().collect();
+        names.sort();
+
+        for name in names {
+            let med = &self.medications[name];
+            let mut row = escape_csv_field(name);
+            let mut taken_count = 0;
+            let mut due_count = 0;
+
+            for (_, date) in &days {
+                let taken = self.doses_taken(name, date) >= med.doses_per_day;
+                let due = !med.is_prn && med.is_due_on(date);
+                row.push_str(if taken { ",1" } else if due { ",0" } else { "," });
+
+                if due {
+                    due_count += 1;
+                    if taken {
+                        taken_count += 1;
+                    }
+                }
+            }
+
+            if med.is_prn {
+                row.push_str(&format!(",N/A,{}\n", med.current_count));
+            } else {
+                let percentage = if due_count == 0 { 0.0 } else { (taken_count as f32 / due_count as f32) * 100.0 };
+                row.push_str(&format!(",{:.1},{}\n", percentage, med.current_count));
+            }
+            csv.push_str(&row);
+        }
+
+        let filename = self.data_dir.join(format!("{}_weekly_report_{}.csv", self.file_stem, week_start));
+
+        let mut file = File::create(&filename).map_err(|e| e.to_string())?;
+        file.write_all(csv.as_bytes()).map_err(|e| e.to_string())?;
+
+        Ok(filename.display().to_string())
+    }
+
+    /// Writes any medication or log changes that have not reached disk yet, then the audit lines
+    /// describing them. Mutators only mark the tracker dirty unless immediate persistence is on;
+    /// dropping the tracker also saves, but batch callers should finish with this to see errors.
+    pub fn save_all(&self) -> io::Result<()> {
+        let mut pending = self.pending_audit.borrow_mut();
+        let writing = self.data_dirty.get() || self.logs_dirty.get() || !pending.is_empty();
+        if self.json_storage {
+            if self.data_dirty.get() || self.logs_dirty.get() {
+                self.save_json()?;
+            }
+        } else {
+            if self.data_dirty.get() {
+                self.write_data()?;
+            }
+            if self.logs_dirty.get() {
+                self.write_logs()?;
+            }
+        }
+        self.data_dirty.set(false);
+        self.logs_dirty.set(false);
+        
+        if !pending.is_empty() {
+            let mut file = OpenOptions::new().create(true).append(true).open(&self.audit_file)?;
+            for line in pending.iter() {
+                writeln!(file, "{}", line)?;
+            }
+            pending.clear();
+        }
+        if writing {
+            self.register_patient()?;
+        }
+        Ok(())
+    }
+
+    /// Adds this patient to patients.txt so their file stem is kept for them. Called only after
+    /// one of their files has been written, so failed opens and lookups leave no entry behind.
+    fn register_patient(&self) -> io::Result<()> {
+        if self.registered.get() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(self.data_dir.join(PATIENT_REGISTRY_FILE))?;
+        writeln!(file, "{},{}", escape_csv_field(&self.file_stem), escape_csv_field(&self.patient_name))?;
+        self.registered.set(true);
+        Ok(())
+    }
+
+    /// Writes every logged dose as one JSON object per line, ordered by date, medication and dose.
+    pub fn export_log_jsonl(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut dates: Vec<&String> = self.daily_logs.keys().collect();
+        dates.sort();
+        
+        for date in dates {
+            let log = &self.daily_logs[date];
+            let mut entries: Vec<(&String, &u32, &DoseRecord)> = log.taken.iter()
+                .flat_map(|(med_name, doses)| doses.iter().map(move |(dose, record)| (med_name, dose, record)))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0).then(a.1.cmp(b.1)));
+            
+            for (med_name, dose, record) in entries {
+                let (status, reason) = match &record.status {
+                    DoseStatus::Taken => ("taken", None),
+                    DoseStatus::Missed => ("missed", None),
+                    DoseStatus::Skipped(reason) => ("skipped", Some(reason.as_str())),
+                };
+                let entry = LogEntryJson {
+                    date,
+                    medication: med_name,
+                    dose: *dose,
+                    status,
+                    time: record.time.as_deref(),
+                    reason,
+                    administered_by: record.administered_by.as_deref(),
+                };
+                serde_json::to_writer(&mut *writer, &entry)?;
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_immediate_persistence(&mut self, immediate: bool) -> io::Result<()> {
+        self.immediate_persistence = immediate;
+        if immediate {
+            self.save_all()?;
+        }
+        Ok(())
+    }
+
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.data_dirty.get() || self.logs_dirty.get()
+    }
+
+    fn save_data(&self) -> io::Result<()> {
+        self.data_dirty.set(true);
+        if self.immediate_persistence {
+            self.save_all()?;
+        }
+        Ok(())
+    }
+
+    fn save_logs(&self) -> io::Result<()> {
+        self.logs_dirty.set(true);
+        if self.immediate_persistence {
+            self.save_all()?;
+        }
+        Ok(())
+    }
+
+    fn write_data(&self) -> io::Result<()> {
+        let tmp_file = self.data_file.with_extension("tmp");
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_file)?;
+        
+        for med in self.medications.values() {
+            writeln!(file, "{}", med.to_csv_line())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_file, &self.data_file)
+    }
+
+    fn load_data(&mut self) -> io::Result<()> {
+        if !self.data_file.exists() {
+            return Ok(());
+        }
+        
+        let file = File::open(&self.data_file)?;
+        let reader = BufReader::new(file);
+        
+        for line in reader.lines().map_while(Result::ok) {
+            let line = line.trim_end_matches('\r').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let med = match Medication::try_from(line) {
+                Ok(med) => med,
+                Err(problem) => {
+                    let data_file = self.data_file.clone();
+                    self.quarantine_line(&data_file, line, &problem)?;
+                    continue;
+                }
+            };
+            self.medications.insert(med.name.clone(), med);
+        }
+        Ok(())
+    }
+
+    /// Moves every log entry dated before `cutoff_date` into `<patient>_logs_archive_<cutoff>.txt`
+    /// (same line format as the active log) and drops it from memory and the active log file.
+    pub fn archive_logs_before(&mut self, cutoff_date: &str) -> io::Result<usize> {
+        let cutoff_date = normalize_date(cutoff_date)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        
+        let mut dates: Vec<String> = self.daily_logs.keys()
+            .filter(|date| date.as_str() < cutoff_date.as_str())
+            .cloned()
+            .collect();
+        if dates.is_empty() {
+            return Ok(0);
+        }
+        dates.sort();
+        
+        let mut archived = 0;
+        let mut lines = String::new();
+        for date in &dates {
+            let log = &self.daily_logs[date];
+            archived += log.taken.values().map(|doses| doses.len()).sum::<usize>();
+            lines.push_str(&log_lines(log));
+        }
+        
+        let archive_file = self.data_dir.join(format!("{}_logs_archive_{}.txt", self.file_stem, cutoff_date));
+        let mut file = OpenOptions::new().create(true).append(true).open(&archive_file)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_all()?;
+        
+        for date in &dates {
+            self.daily_logs.remove(date);
+        }
+        self.history.retain(|action| action.date >= cutoff_date);
+        self.save_logs()?;
+        self.save_all()?;
+        Ok(archived)
+    }
+
+    fn write_logs(&self) -> io::Result<()> {
+        let tmp_file = self.log_file.with_extension("tmp");
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_file)?;
+        
+        for log in self.daily_logs.values() {
+            file.write_all(log_lines(log).as_bytes())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_file, &self.log_file)?;
+        
+        let tmp_file = self.snooze_file.with_extension("tmp");
+        let mut file = File::create(&tmp_file)?;
+        for log in self.daily_logs.values() {
+            for (med_name, until) in &log.snoozed_until {
+                let line = format!("{},{},{}\n",
+                    escape_csv_field(&log.date),
+                    escape_csv_field(med_name),
+                    until
+                );
+                file.write_all(line.as_bytes())?;
+            }
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_file, &self.snooze_file)?;
+        
+        let tmp_file = self.nag_file.with_extension("tmp");
+        let mut file = File::create(&tmp_file)?;
+        for log in self.daily_logs.values() {
+            let mut names: HashSet<&String> = log.reminders_shown.keys().collect();
+            names.extend(&log.acknowledged);
+            for med_name in names {
+                let line = format!("{},{},{},{}\n",
+                    escape_csv_field(&log.date),
+                    escape_csv_field(med_name),
+                    log.reminders_shown.get(med_name).copied().unwrap_or(0),
+                    if log.acknowledged.contains(med_name) { "1" } else { "0" }
+                );
+                file.write_all(line.as_bytes())?;
+            }
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_file, &self.nag_file)
+    }
+
+    fn load_logs(&mut self) -> io::Result<()> {
+        if !self.log_file.exists() {
+            return Ok(());
+        }
+        
+        let file = File::open(&self.log_file)?;
+        let reader = BufReader::new(file);
+        
+        for line in reader.lines().map_while(Result::ok) {
+            let line = line.trim_end_matches('\r').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts = parse_csv_line(line);
+            let problem = if parts.len() < 3 {
+                Some(format!("expected at least 3 fields, found {}", parts.len()))
+            } else if NaiveDate::parse_from_str(&parts[0], DATE_FORMAT).is_err() {
+                Some(format!("invalid date '{}'", parts[0]))
+            } else if parts.get(3).is_some_and(|dose| !dose.is_empty() && dose.parse::<u32>().is_err()) {
+                Some(format!("invalid dose number '{}'", parts[3]))
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                let log_file = self.log_file.clone();
+                self.quarantine_line(&log_file, line, &problem)?;
+                continue;
+            }
+            
+            let date = parts[0].clone();
+            let dose = parts.get(3).and_then(|v| v.parse().ok()).unwrap_or(1);
+            let log = self.daily_logs.entry(date.clone()).or_insert_with(|| DailyLog::new(&date));
+            let status = match parts[2].as_str() {
+                "1" => DoseStatus::Taken,
+                "S" => DoseStatus::Skipped(parts.get(5).cloned().unwrap_or_default()),
+                _ => DoseStatus::Missed,
+            };
+            let record = DoseRecord {
+                deducted: status == DoseStatus::Taken && parts.get(7).is_none_or(|deducted| deducted != "0"),
+                status,
+                time: parts.get(4).filter(|time| !time.is_empty()).cloned(),
+                administered_by: parts.get(6).filter(|name| !name.is_empty()).cloned(),
+            };
+            let doses = log.taken.entry(parts[1].clone()).or_default();
+            let keep_existing = doses.get(&dose)
+                .is_some_and(|existing| existing.status.precedence() > record.status.precedence());
+            if !keep_existing {
+                doses.insert(dose, record);
+            }
+        }
+        
+        if !self.snooze_file.exists() {
+            return Ok(());
+        }
+        
+        let file = File::open(&self.snooze_file)?;
+        let reader = BufReader::new(file);
+        
+        for line in reader.lines().map_while(Result::ok) {
+            let line = line.trim_end_matches('\r').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts = parse_csv_line(line);
+            if parts.len() == 3 {
+                let date = parts[0].clone();
+                let log = self.daily_logs.entry(date.clone()).or_insert_with(|| DailyLog::new(&date));
+                log.snoozed_until.insert(parts[1].clone(), parts[2].clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn load_nags(&mut self) -> io::Result<()> {
+        if !self.nag_file.exists() {
+            return Ok(());
+        }
+        
+        let file = File::open(&self.nag_file)?;
+        let reader = BufReader::new(file);
+        
+        for line in reader.lines().map_while(Result::ok) {
+            let line = line.trim_end_matches('\r').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts = parse_csv_line(line);
+            if parts.len() == 4 {
+                let date = parts[0].clone();
+                let log = self.daily_logs.entry(date.clone()).or_insert_with(|| DailyLog::new(&date));
+                if let Ok(count) = parts[2].parse() {
+                    log.reminders_shown.insert(parts[1].clone(), count);
+                }
+                if parts[3] == "1" {
+                    log.acknowledged.insert(parts[1].clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load_config(&mut self) -> io::Result<()> {
+        if !self.config_file.exists() {
+            return Ok(());
+        }
+        
+        let contents = fs::read_to_string(&self.config_file)?;
+        for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match line.split_once('=') {
+                Some(("default_quantity", value)) => match value.trim().parse() {
+                    Ok(quantity) => self.default_quantity = quantity,
+                    Err(_) => self.load_warnings.push(format!("config.txt: invalid default_quantity '{}', using {}",
+                        value.trim(), DEFAULT_STARTING_QUANTITY)),
+                },
+                Some(("week_start", value)) => match value.trim().parse() {
+                    Ok(day) => self.week_start_day = day,
+                    Err(_) => self.load_warnings.push(format!("config.txt: invalid week_start '{}', using Mon", value.trim())),
+                },
+                _ => self.load_warnings.push(format!("config.txt: ignoring unknown setting '{}'", line)),
+            }
+        }
+        Ok(())
+    }
+
+    fn save_config(&self) -> io::Result<()> {
+        fs::write(&self.config_file, format!("default_quantity={}\nweek_start={}\n", self.default_quantity, self.week_start_day))?;
+        self.register_patient()
+    }
+
+    pub fn default_quantity(&self) -> u32 {
+        self.default_quantity
+    }
+
+    pub fn set_default_quantity(&mut self, quantity: u32) -> Result<(), String> {
+        if quantity == 0 {
+            return Err("Default quantity must be greater than zero".to_string());
+        }
+        self.default_quantity = quantity;
+        self.save_config().map_err(|e| e.to_string())
+    }
+
+    fn load_groups(&mut self) -> io::Result<()> {
+        if !self.group_file.exists() {
+            return Ok(());
+        }
+        
+        let contents = fs::read_to_string(&self.group_file)?;
+        for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut parts = parse_csv_line(line);
+            if parts.len() < 2 {
+                self.load_warnings.push(format!("groups.txt: ignoring group with no members '{}'", line));
+                continue;
+            }
+            let group = parts.remove(0);
+            self.groups.insert(group, parts);
+        }
+        Ok(())
+    }
+
+    fn save_groups(&self) -> io::Result<()> {
+        let mut contents = String::new();
+        for (group, members) in self.groups() {
+            let fields: Vec<String> = std::iter::once(group).chain(members).map(|field| escape_csv_field(&field)).collect();
+            contents.push_str(&fields.join(","));
+            contents.push('\n');
+        }
+        fs::write(&self.group_file, contents)?;
+        self.register_patient()
+    }
+
+    pub fn week_start_day(&self) -> Weekday {
+        self.week_start_day
+    }
+
+    pub fn set_week_start_day(&mut self, day: Weekday) -> Result<(), String> {
+        self.week_start_day = day;
+        self.save_config().map_err(|e| e.to_string())
+    }
+
+    /// The first day of the week containing `date`, counting from the configured start day.
+    pub fn week_start(&self, date: &str) -> String {
+        let Ok(parsed) = NaiveDate::parse_from_str(date, DATE_FORMAT) else {
+            return date.to_string();
+        };
+        let days_back = (parsed.weekday().num_days_from_monday() + 7 - self.week_start_day.num_days_from_monday()) % 7;
+        add_days(date, -(days_back as i64)).unwrap_or_else(|| date.to_string())
+    }
+
+    /// The seven (label, date) pairs of the week containing `date`.
+    fn week_days(&self, date: &str) -> Vec<(String, String)> {
+        let week_start = self.week_start(date);
+        (0..7)
+            .filter_map(|i| add_days(&week_start, i))
+            .map(|date| {
+                let label = NaiveDate::parse_from_str(&date, DATE_FORMAT)
+                    .map(|day| day.weekday().to_string())
+                    .unwrap_or_default();
+                (label, date)
+            })
+            .collect()
+    }
+
+    /// Records a warning for a line that could not be parsed and copies it to `<file>.corrupt`
+    /// so it survives the next save for manual recovery.
+    fn quarantine_line(&mut self, source: &Path, line: &str, problem: &str) -> io::Result<()> {
+        let corrupt_file = source.with_extension("corrupt");
+        let already_saved = fs::read_to_string(&corrupt_file)
+            .is_ok_and(|contents| contents.lines().any(|saved| saved == line));
+        if !already_saved {
+            let mut file = OpenOptions::new().create(true).append(true).open(&corrupt_file)?;
+            writeln!(file, "{}", line)?;
+        }
+        
+        let file_name = source.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        self.load_warnings.push(format!("{}: {} ({}) - saved to {}", file_name, line, problem, corrupt_file.display()));
+        Ok(())
+    }
+
+    pub fn load_warnings(&self) -> &[String] {
+        &self.load_warnings
+    }
+
+    pub fn verify_integrity(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut seen: HashMap<String, &str> = HashMap::new();
+
+        for name in &self.display_order {
+            let med = &self.medications[name];
+            if med.name != *name {
+                problems.push(format!("{} is stored under the name {}", med.name, name));
+            }
+            if let Some(other) = seen.insert(name.to_lowercase(), name) {
+                problems.push(format!("{} and {} are duplicate entries", other, name));
+            }
+            if med.current_count > med.total_prescribed {
+                problems.push(format!("{} has {} doses left but only {} were ever prescribed",
+                    name, med.current_count, med.total_prescribed));
+            }
+
+            let logged = med.units_per_dose.saturating_mul(self.total_doses_deducted(name));
+            if med.current_count <= med.total_prescribed && med.current_count.saturating_add(logged) > med.total_prescribed {
+                problems.push(format!("{} has {} doses left and {} logged as taken, more than the {} prescribed",
+                    name, med.current_count, logged, med.total_prescribed));
+            }
+        }
+
+        for name in self.orphaned_medications() {
+            problems.push(format!("Logs refer to {}, which is not in the medication list", name));
+        }
+        problems
+    }
+
+    /// Names that appear in the logs but not in the medication list, e.g. after a med was deleted
+    /// from the meds file by hand. Their entries are kept on disk but left out of summaries.
+    pub fn orphaned_medications(&self) -> Vec<String> {
+        let mut orphans: Vec<String> = self.daily_logs.values()
+            .flat_map(|log| log.taken.keys())
+            .filter(|name| !self.medications.contains_key(*name))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    /// Clamps each count so it fits within what was prescribed minus what the logs say was taken,
+    /// and drops log entries for medications that no longer exist. Returns the number of fixes,
+    /// or the error if they could not be saved.
+    pub fn repair(&mut self) -> io::Result<usize> {
+        let mut fixes = 0;
+
+        for name in self.display_order.clone() {
+            let taken = self.total_doses_deducted(&name);
+            let med = self.medications.get_mut(&name).unwrap();
+            let logged = med.units_per_dose.saturating_mul(taken);
+            if med.name != name {
+                med.name = name.clone();
+                fixes += 1;
+            }
+            let limit = med.total_prescribed.saturating_sub(logged);
+            if med.current_count > limit {
+                med.current_count = limit;
+                fixes += 1;
+            }
+        }
+
+        let medications = &self.medications;
+        for log in self.daily_logs.values_mut() {
+            let before = log.taken.len();
+            log.taken.retain(|name, _| medications.contains_key(name));
+            fixes += before - log.taken.len();
+            log.snoozed_until.retain(|name, _| medications.contains_key(name));
+            log.reminders_shown.retain(|name, _| medications.contains_key(name));
+            log.acknowledged.retain(|name| medications.contains_key(name));
+        }
+        self.history.retain(|action| medications.contains_key(&action.med_name));
+
+        if fixes > 0 {
+            self.save_data()?;
+            self.save_logs()?;
+            self.audit(&format!("repair,{}", fixes))?;
+        }
+        Ok(fixes)
+    }
+
+    /// Taken doses that came out of the supply, across every logged day.
+    fn total_doses_deducted(&self, med_name: &str) -> u32 {
+        self.daily_logs.values()
+            .filter_map(|log| log.taken.get(med_name))
+            .map(|doses| doses.values().filter(|record| record.is_taken() && record.deducted).count() as u32)
+            .sum()
+    }
+
+    fn save_json(&self) -> io::Result<()> {
+        let data = TrackerData {
+            medications: self.medications.clone(),
+            daily_logs: self.daily_logs.clone(),
+        };
+        
+        let json = serde_json::to_string_pretty(&data)?;
+        let tmp_file = self.json_file.with_extension("tmp");
+        let mut file = File::create(&tmp_file)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_file, &self.json_file)
+    }
+
+    fn load_json(&mut self) -> io::Result<()> {
+        let file = File::open(&self.json_file)?;
+        let data: TrackerData = serde_json::from_reader(BufReader::new(file))?;
+        
+        self.medications = data.medications;
+        for med in self.medications.values_mut() {
+            med.is_prn |= is_as_needed(&med.time_of_day);
+        }
+        self.daily_logs = data.daily_logs;
+        Ok(())
+    }
+
+    pub fn export_ics(&self, start_date: &str, days: u32) -> Result<String, String> {
+        let start_date = &normalize_date(start_date)?;
+        let start = NaiveDate::parse_from_str(start_date, DATE_FORMAT).map_err(|e| e.to_string())?;
+        if days == 0 {
+            return Err("Number of days must be at least 1".to_string());
+        }
+        
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut ics = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//MediTrack//Medication Tracker//EN\r\nCALSCALE:GREGORIAN\r\n");
+        
+        let mut names: Vec<&String> = self.medications.keys().collect();
+        names.sort();
+        
+        for name in names {
+            let med = &self.medications[name];
+            if med.is_prn {
+                continue;
+            }
+            let (hour, minute) = match parse_schedule(&med.time_of_day).ok().and_then(|s| s.clock_time()) {
+                Some(time) => time,
+                None => continue,
+            };
+            let first_due = (0..days as i64)
+                .map(|i| start + Duration::days(i))
+                .find(|day| med.is_due_on(&day.format(DATE_FORMAT).to_string()));
+            let first_due = match first_due {
+                Some(day) => day,
+                None => continue,
+            };
+            let mut last = start + Duration::days(days as i64 - 1);
+            if let Some(end) = med.end_date.as_deref().and_then(|end| NaiveDate::parse_from_str(end, DATE_FORMAT).ok()) {
+                last = last.min(end);
+            }
+            let until = last.format("%Y%m%dT235959");
+            let rule = match &med.frequency {
+                Frequency::Daily => format!("FREQ=DAILY;UNTIL={}", until),
+                Frequency::WeeklyOn(weekdays) => {
+                    let by_day: Vec<String> = weekdays.iter()
+                        .map(|day| day.to_string().to_uppercase().chars().take(2).collect())
+                        .collect();
+                    format!("FREQ=WEEKLY;BYDAY={};UNTIL={}", by_day.join(","), until)
+                }
+                Frequency::EveryNDays(n) => format!("FREQ=DAILY;INTERVAL={};UNTIL={}", n, until),
+            };
+            
+            push_ics_line(&mut ics, "BEGIN:VEVENT");
+            push_ics_line(&mut ics, &format!("UID:{}-{}-{}@meditrack",
+                start.format("%Y%m%d"),
+                self.file_stem,
+                name.replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+            ));
+            push_ics_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+            push_ics_line(&mut ics, &format!("DTSTART:{}T{:02}{:02}00", first_due.format("%Y%m%d"), hour, minute));
+            push_ics_line(&mut ics, "DURATION:PT15M");
+            push_ics_line(&mut ics, &format!("RRULE:{}", rule));
+            push_ics_line(&mut ics, &format!("SUMMARY:{}", escape_ics_text(&format!("Take {} ({})", name, med.dosage))));
+            if !med.notes.is_empty() {
+                push_ics_line(&mut ics, &format!("DESCRIPTION:{}", escape_ics_text(&med.notes)));
+            }
+            push_ics_line(&mut ics, "END:VEVENT");
+        }
+        ics.push_str("END:VCALENDAR\r\n");
+        
+        let filename = self.data_dir.join(format!("{}_reminders_{}.ics", self.file_stem, start_date));
+        
+        let mut file = File::create(&filename).map_err(|e| e.to_string())?;
+        file.write_all(ics.as_bytes()).map_err(|e| e.to_string())?;
+        
+        Ok(filename.display().to_string())
+    }
+
+    pub fn backup(&self, dest_dir: &Path) -> io::Result<PathBuf> {
+        self.save_data()?;
+        self.save_logs()?;
+        self.save_all()?;
+        
+        let stamp = Local::now().format("%Y%m%d_%H%M%S");
+        let backup_dir = dest_dir.join(format!("{}_backup_{}", self.file_stem, stamp));
+        fs::create_dir_all(&backup_dir)?;
+        
+        for path in self.patient_files(&self.data_dir)? {
+            if let Some(file_name) = path.file_name() {
+                fs::copy(&path, backup_dir.join(file_name))?;
+            }
+        }
+        Ok(backup_dir)
+    }
+
+    pub fn restore(&mut self, backup_dir: &Path) -> io::Result<()> {
+        let has_file = |path: &Path| path.file_name().is_some_and(|name| backup_dir.join(name).is_file());
+        let has_json = has_file(&self.json_file);
+        
+        if !(has_json || has_file(&self.data_file) && has_file(&self.log_file)) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not contain a backup for {}", backup_dir.display(), self.patient_name),
+            ));
+        }
+        // Flush now so pending audit lines are kept and nothing unsaved is written over the restore.
+        self.save_all()?;
+        
+        for path in self.patient_files(backup_dir)? {
+            if let Some(file_name) = path.file_name() {
+                if Some(file_name) != self.audit_file.file_name() {
+                    fs::copy(&path, self.data_dir.join(file_name))?;
+                }
+            }
+        }
+        if !has_json && self.json_file.exists() {
+            fs::remove_file(&self.json_file)?;
+        }
+        
+        let patient_name = self.patient_name.clone();
+        self.reopen(&patient_name)
+    }
+
+    fn patient_files(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let prefix = format!("{}_", self.file_stem);
+        let mut files = Vec::new();
+        
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let matches = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .is_some_and(|rest| !rest.ends_with(".tmp")
+                    && (PATIENT_FILE_SUFFIXES.contains(&rest)
+                        || REPORT_FILE_PREFIXES.iter().any(|report| rest.starts_with(report))));
+            if matches && path.is_file() {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    pub fn enable_json_storage(&mut self) -> io::Result<()> {
+        self.json_storage = true;
+        self.save_json()?;
+        self.data_dirty.set(false);
+        self.logs_dirty.set(false);
+        self.register_patient()
+    }
+
+    pub fn list_medications(&self, today: &str) -> Vec<String> {
+        let mut meds: Vec<&Medication> = self.medications.values().filter(|med| !med.paused).collect();
+        meds.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.name.cmp(&b.name)));
+        
+        meds.into_iter()
+            .map(|med| {
+                let mut line = med.to_string();
+                if let Ok(date) = self.run_out_date(&med.name, today) {
+                    line.push_str(&format!(" - runs out {}", date));
+                }
+                match med.cost_per_dose() {
+                    Some(cost) if med.is_prn => line.push_str(&format!(" - ${:.2}/dose", cost)),
+                    Some(cost) => line.push_str(&format!(" - ${:.2}/dose (~${:.2}/month)", cost, cost * med.doses_per_month())),
+                    None => line.push_str(" - no cost set"),
+                }
+                line
+            })
+            .collect()
+    }
+
+    pub fn paused_medications(&self) -> Vec<String> {
+        let mut meds: Vec<&Medication> = self.medications.values().filter(|med| med.paused).collect();
+        meds.sort_by(|a, b| a.name.cmp(&b.name));
+        meds.into_iter().map(|med| med.to_string()).collect()
+    }
+
+    pub fn days_until_empty(&self, name: &str) -> Result<u32, String> {
+        let med = self.medications.get(name).ok_or("Medication not found")?;
+        if med.is_prn {
+            return Err(format!("{} is taken as needed, so usage is irregular", name));
+        }
+        Ok(med.current_count.times(med.units_per_dose.saturating_mul(med.doses_per_day.max(1))))
+    }
+
+    pub fn run_out_date(&self, name: &str, today: &str) -> Result<String, String> {
+        let today = &normalize_date(today)?;
+        let days = self.days_until_empty(name)?;
+        add_days(today, days as i64).ok_or_else(|| format!("{} has more supply than the calendar can show", name))
+    }
+
+    pub fn low_stock_medications(&self) -> Vec<(String, Quantity)> {
+        let mut low: Vec<(String, Quantity)> = self.medications.values()
+            .filter(|med| med.current_count <= Quantity::whole(med.refill_threshold))
+            .map(|med| (med.name.clone(), med.current_count))
+            .collect();
+        low.sort();
+        low
+    }
+
+    pub fn supply_warnings(&self) -> Vec<String> {
+        let mut low: Vec<(bool, Option<u32>, Quantity, String)> = self.low_stock_medications()
+            .into_iter()
+            .map(|(name, count)| {
+                let days = self.days_until_empty(&name).ok();
+                (days.is_none(), days, count, name)
+            })
+            .collect();
+        low.sort();
+        
+        low.into_iter()
+            .map(|(_, days, count, name)| match days {
+                _ if count.is_zero() => format!("{} is out of stock", name),
+                Some(days) => format!("{}: {} left, about {} day{} of supply", name, count, days, if days == 1 { "" } else { "s" }),
+                None => format!("{}: {} left", name, count),
+            })
+            .collect()
+    }
+
+    pub fn find_medications(&self, query: &str) -> Vec<&Medication> {
+        let query = query.trim().to_lowercase();
+        let mut found: Vec<&Medication> = self.medications.values()
+            .filter(|med| {
+                query.is_empty()
+                    || med.name.to_lowercase().contains(&query)
+                    || med.time_of_day.to_lowercase().contains(&query)
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+
+    pub fn medications_at_time(&self, time: &str) -> Vec<&Medication> {
+        let time = time.trim();
+        let mut found: Vec<&Medication> = self.medications.values()
+            .filter(|med| med.time_of_day.eq_ignore_ascii_case(time))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+
+    pub fn check_interactions(&self) -> Vec<(String, String, String)> {
+        let mut names: Vec<&String> = self.medications.keys().collect();
+        names.sort();
+        
+        let mut warnings = Vec::new();
+        for (i, med_a) in names.iter().enumerate() {
+            for med_b in &names[i + 1..] {
+                let (Some(drug_a), Some(drug_b)) = (normalize_drug_name(med_a), normalize_drug_name(med_b)) else {
+                    continue;
+                };
+                
+                for (first, second, warning) in DRUG_INTERACTIONS {
+                    if (drug_a == *first && drug_b == *second) || (drug_a == *second && drug_b == *first) {
+                        warnings.push((med_a.to_string(), med_b.to_string(), warning.to_string()));
+                    }
+                }
+            }
+        }
+        warnings
+    }
+
+    pub fn refill_medication(&mut self, name: &str, amount: Quantity) -> Result<(), String> {
+        if amount.is_zero() {
+            return Err("Refill amount must be greater than zero".to_string());
+        }
+
+        match self.medications.get_mut(name) {
+            Some(med) => {
+                let new_count = med.current_count
+                    .checked_add(amount)
+                    .ok_or("Refill amount is too large")?;
+                if let Some(max) = med.max_count {
+                    if new_count > Quantity::whole(max) {
+                        return Err(format!("Refill would bring {} to {}, above the maximum of {}",
+                            name, new_count, max));
+                    }
+                }
+
+                med.current_count = new_count;
+                med.total_prescribed = med.total_prescribed.saturating_add(amount);
+                if med.refill_size > 0 && amount == Quantity::whole(med.refill_size) {
+                    med.refills_remaining = med.refills_remaining.saturating_sub(1);
+                }
+                self.save_data().map_err(|e| e.to_string())?;
+                self.audit(&format!("refill_medication,{},{}", escape_csv_field(name), amount)).map_err(|e| e.to_string())
+            }
+            None => Err("Medication not found".to_string()),
+        }
+    }
+
+    /// Refills every medication in one go: back up to its prescribed total when `to_full`, otherwise
+    /// by a flat `amount` each. Nothing changes if any medication would go over its maximum.
+    pub fn refill_all(&mut self, to_full: bool, amount: Option<u32>) -> Result<usize, String> {
+        let amount = match (to_full, amount) {
+            (true, _) => None,
+            (false, Some(amount)) if amount > 0 => Some(Quantity::whole(amount)),
+            (false, _) => return Err("Refill amount must be greater than zero".to_string()),
+        };
+        
+        let mut refills = Vec::new();
+        for name in self.medication_names() {
+            let med = &self.medications[&name];
+            let new_count = match amount {
+                Some(amount) => med.current_count.checked_add(amount).ok_or("Refill amount is too large")?,
+                None => med.total_prescribed,
+            };
+            if new_count <= med.current_count {
+                continue;
+            }
+            if let Some(max) = med.max_count {
+                if new_count > Quantity::whole(max) {
+                    return Err(format!("Refill would bring {} to {}, above the maximum of {}", name, new_count, max));
+                }
+            }
+            refills.push((name, new_count));
+        }
+        if refills.is_empty() {
+            return Ok(0);
+        }
+        
+        for (name, new_count) in &refills {
+            let Some(med) = self.medications.get_mut(name) else {
+                continue;
+            };
+            let added = new_count.saturating_sub(med.current_count);
+            med.current_count = *new_count;
+            if amount.is_some() {
+                med.total_prescribed = med.total_prescribed.saturating_add(added);
+            }
+            if med.refill_size > 0 && added == Quantity::whole(med.refill_size) {
+                med.refills_remaining = med.refills_remaining.saturating_sub(1);
+            }
+        }
+        self.save_data().map_err(|e| e.to_string())?;
+        let how = amount.map_or_else(|| "full".to_string(), |amount| amount.to_string());
+        self.audit(&format!("refill_all,{}", how)).map_err(|e| e.to_string())?;
+        Ok(refills.len())
+    }
+
+    pub fn set_refills(&mut self, name: &str, refill_size: u32, refills_remaining: u32) -> Result<(), String> {
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.refill_size = refill_size;
+        med.refills_remaining = refills_remaining;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn refills_left(&self, name: &str) -> Result<u32, String> {
+        let med = self.medications.get(name).ok_or("Medication not found")?;
+        if med.refill_size == 0 {
+            return Err(format!("No prescription refills are set up for {}", name));
+        }
+        Ok(med.refills_remaining)
+    }
+
+    /// Low-stock medications with no refills left on their prescription.
+    pub fn refill_warnings(&self) -> Vec<String> {
+        self.low_stock_medications()
+            .into_iter()
+            .filter(|(name, _)| self.refills_left(name) == Ok(0))
+            .map(|(name, _)| format!("{}: no refills left — contact doctor", name))
+            .collect()
+    }
+
+    pub fn set_max_count(&mut self, name: &str, max_count: Option<u32>) -> Result<(), String> {
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.max_count = max_count;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn set_units_per_dose(&mut self, name: &str, units_per_dose: Quantity) -> Result<(), String> {
+        if units_per_dose.is_zero() {
+            return Err("Amount per dose must be greater than zero".to_string());
+        }
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.units_per_dose = units_per_dose;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    /// Limits a medication to a course of treatment. Blank dates leave that side open-ended.
+    pub fn set_course_dates(&mut self, name: &str, start: &str, end: &str) -> Result<(), String> {
+        let parse = |date: &str| -> Result<Option<String>, String> {
+            if date.trim().is_empty() { Ok(None) } else { normalize_date(date.trim()).map(Some) }
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if let (Some(start), Some(end)) = (&start, &end) {
+            if start > end {
+                return Err(format!("The course cannot end ({}) before it starts ({})", end, start));
+            }
+        }
+        
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.start_date = start;
+        med.end_date = end;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    /// Medications whose course ended more than a few days before `today`, ready to be removed.
+    pub fn finished_courses(&self, today: &str) -> Vec<(String, String)> {
+        let Some(today) = normalize_date(today).ok().and_then(|today| NaiveDate::parse_from_str(&today, DATE_FORMAT).ok()) else {
+            return Vec::new();
+        };
+        self.display_order.iter()
+            .filter_map(|name| {
+                let end = self.medications[name].end_date.as_ref()?;
+                let ended = NaiveDate::parse_from_str(end, DATE_FORMAT).ok()?;
+                ((today - ended).num_days() > COURSE_ENDED_GRACE_DAYS).then(|| (name.clone(), end.clone()))
+            })
+            .collect()
+    }
+
+    pub fn set_paused(&mut self, name: &str, paused: bool) -> Result<(), String> {
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        let today = get_today();
+        if paused && !med.paused {
+            med.pauses.push((today, None));
+        } else if !paused && med.paused {
+            // A pause undone the same day leaves nothing to record.
+            if med.pauses.last().is_some_and(|(from, until)| until.is_none() && *from == today) {
+                med.pauses.pop();
+            } else if let Some((_, until @ None)) = med.pauses.last_mut() {
+                *until = Some(today);
+            }
+        }
+        med.paused = paused;
+        self.save_data().map_err(|e| e.to_string())?;
+        let event = if paused { "pause_medication" } else { "resume_medication" };
+        self.audit(&format!("{},{}", event, escape_csv_field(name))).map_err(|e| e.to_string())
+    }
+
+    pub fn set_prn(&mut self, name: &str, is_prn: bool) -> Result<(), String> {
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        if !is_prn && is_as_needed(&med.time_of_day) {
+            return Err(format!("{} is scheduled as needed - give it a time of day first", name));
+        }
+        med.is_prn = is_prn;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn set_frequency(&mut self, name: &str, frequency: Frequency) -> Result<(), String> {
+        if let Frequency::WeeklyOn(days) = &frequency {
+            if days.is_empty() {
+                return Err("Weekly schedule needs at least one day".to_string());
+            }
+        }
+        if frequency == Frequency::EveryNDays(0) {
+            return Err("Interval must be at least 1 day".to_string());
+        }
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.interval_start = match frequency {
+            Frequency::EveryNDays(_) if med.frequency == frequency => med.interval_start.take().or_else(|| Some(get_today())),
+            Frequency::EveryNDays(_) => Some(get_today()),
+            _ => None,
+        };
+        med.frequency = frequency;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn set_cost_per_unit(&mut self, name: &str, cost_per_unit: Option<f32>) -> Result<(), String> {
+        if cost_per_unit.is_some_and(|cost| !cost.is_finite() || cost < 0.0) {
+            return Err("Cost must be zero or a positive amount".to_string());
+        }
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.cost_per_unit = cost_per_unit;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    /// Projected spend for 30 days of scheduled doses. As-needed meds and meds without a price
+    /// contribute nothing; see `unpriced_medications`.
+    pub fn estimated_monthly_cost(&self) -> f32 {
+        self.medications.values()
+            .filter(|med| !med.is_prn)
+            .map(|med| med.cost_per_dose().unwrap_or(0.0) * med.doses_per_month())
+            .sum()
+    }
+
+    pub fn actual_monthly_cost(&self, year: i32, month: u32) -> f32 {
+        let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return 0.0;
+        };
+        let first_date = first.format(DATE_FORMAT).to_string();
+        
+        (0..days_in_month(year, month))
+            .filter_map(|day| add_days(&first_date, day as i64))
+            .map(|date| self.medications.values()
+                .map(|med| self.doses_taken(&med.name, &date) as f32 * med.cost_per_dose().unwrap_or(0.0))
+                .sum::<f32>())
+            .sum()
+    }
+
+    pub fn unpriced_medications(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.medications.values()
+            .filter(|med| med.cost_per_unit.is_none())
+            .map(|med| med.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Medications whose dosage is free text rather than an amount and a known unit.
+    pub fn unrecognized_dosages(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.medications.values()
+            .filter(|med| med.parsed_dosage.is_none())
+            .map(|med| med.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn set_grace_minutes(&mut self, name: &str, grace_minutes: u32) -> Result<(), String> {
+        if grace_minutes > 24 * 60 {
+            return Err("Grace period cannot be longer than a day".to_string());
+        }
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.grace_minutes = grace_minutes;
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn set_priority(&mut self, name: &str, priority: Priority) -> Result<(), String> {
+        let med = self.medications.get_mut(name).ok_or("Medication not found")?;
+        med.priority = priority;
+        self.refresh_display_order();
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    pub fn edit_medication(&mut self, name: &str, new_dosage: Option<String>, new_time: Option<String>) -> Result<(), String> {
+        let med = match self.medications.get_mut(name) {
+            Some(med) => med,
+            None => return Err("Medication not found".to_string()),
+        };
+
+        if let Some(dosage) = new_dosage {
+            if dosage.trim().is_empty() {
+                return Err("Dosage cannot be empty".to_string());
+            }
+            if dosage.trim().starts_with('-') {
+                return Err("Dosage cannot be negative".to_string());
+            }
+            med.parsed_dosage = parse_dosage(&dosage).ok();
+            med.dosage = dosage;
+        }
+        if let Some(time_of_day) = new_time {
+            if is_as_needed(&time_of_day) {
+                med.is_prn = true;
+            } else if is_as_needed(&med.time_of_day) {
+                med.is_prn = false;
+            }
+            med.time_of_day = time_of_day;
+        }
+
+        self.refresh_display_order();
+        self.save_data().map_err(|e| e.to_string())
+    }
+
+    /// Bundles medications taken together (e.g. a morning pill packet) so they can be marked in one go.
+    pub fn add_group(&mut self, group_name: &str, members: Vec<String>) -> Result<(), String> {
+        let group_name = group_name.trim();
+        if group_name.is_empty() {
+            return Err("Group name cannot be empty".to_string());
+        }
+        if self.medications.contains_key(group_name) {
+            return Err(format!("{} is already a medication name", group_name));
+        }
+        if self.groups.contains_key(group_name) {
+            return Err(format!("A group named {} already exists", group_name));
+        }
+        
+        let mut resolved: Vec<String> = Vec::new();
+        for member in &members {
+            let name = self.resolve_medication(member)?;
+            if self.medications[&name].is_prn {
+                return Err(format!("{} is taken as needed and cannot be part of a group", name));
+            }
+            if resolved.contains(&name) {
+                return Err(format!("{} is listed twice", name));
+            }
+            resolved.push(name);
+        }
+        if resolved.len() < 2 {
+            return Err("A group needs at least two medications".to_string());
+        }
+        
+        let audit_members: Vec<String> = resolved.iter().map(|name| escape_csv_field(name)).collect();
+        self.groups.insert(group_name.to_string(), resolved);
+        self.save_groups().map_err(|e| e.to_string())?;
+        self.audit(&format!("add_group,{},{}", escape_csv_field(group_name), audit_members.join(",")))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn remove_group(&mut self, group_name: &str) -> Result<(), String> {
+        if self.groups.remove(group_name).is_none() {
+            return Err("Group not found".to_string());
+        }
+        self.save_groups().map_err(|e| e.to_string())?;
+        self.audit(&format!("remove_group,{}", escape_csv_field(group_name))).map_err(|e| e.to_string())
+    }
+
+    /// Groups sorted by name, each with its members in the order they were given.
+    pub fn groups(&self) -> Vec<(String, Vec<String>)> {
+        let mut groups: Vec<(String, Vec<String>)> = self.groups.iter()
+            .map(|(group, members)| (group.clone(), members.clone()))
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Marks the next untaken dose of every member taken and returns how many doses were recorded.
+    pub fn mark_group_taken(&mut self, group_name: &str, date: &str) -> Result<usize, String> {
+        let date = &normalize_date(date)?;
+        let members = self.groups.get(group_name).cloned().ok_or("Group not found")?;
+        
+        let mut marked = 0;
+        for member in members {
+            if let Ok(dose) = self.next_untaken_dose(&member, date) {
+                self.mark_taken(&member, date, dose, true)?;
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+
+    /// Collapses reminders for members of the same group into a single line named after the group.
+    pub fn group_reminder_entries(&self, entries: Vec<(String, String)>) -> Vec<(String, String)> {
+        let mut grouped: Vec<(String, String)> = Vec::new();
+        for (med, message) in &entries {
+            let group = self.groups().into_iter().find(|(_, members)| members.contains(med));
+            let Some((group, members)) = group else {
+                grouped.push((med.clone(), message.clone()));
+                continue;
+            };
+            if grouped.iter().any(|(name, _)| *name == group) {
+                continue;
+            }
+            let messages: Vec<&str> = entries.iter()
+                .filter(|(other, _)| members.contains(other))
+                .map(|(_, message)| message.as_str())
+                .collect();
+            let line = format!("{}: {}", group, messages.join("; "));
+            grouped.push((group, line));
+        }
+        grouped
+    }
+
+    pub fn remove_medication(&mut self, name: &str) -> Result<(), String> {
+        if self.medications.remove(name).is_none() {
+            return Err("Medication not found".to_string());
+        }
+        self.refresh_display_order();
+
+        for log in self.daily_logs.values_mut() {
+            log.taken.remove(name);
+            log.snoozed_until.remove(name);
+            log.reminders_shown.remove(name);
+            log.acknowledged.remove(name);
+        }
+        if self.groups.values().any(|members| members.iter().any(|member| member == name)) {
+            for members in self.groups.values_mut() {
+                members.retain(|member| member != name);
+            }
+            self.groups.retain(|_, members| members.len() >= 2);
+            self.save_groups().map_err(|e| e.to_string())?;
+        }
+
+        self.save_data().map_err(|e| e.to_string())?;
+        self.save_logs().map_err(|e| e.to_string())?;
+        self.audit(&format!("remove_medication,{}", escape_csv_field(name))).map_err(|e| e.to_string())
+    }
+
+    pub fn rename_medication(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("Medication name cannot be empty".to_string());
+        }
+        if self.medications.contains_key(new_name) {
+            return Err(format!("A medication named {} already exists", new_name));
+        }
+        let mut med = self.medications.remove(old_name)
+            .ok_or_else(|| "Medication not found".to_string())?;
+        med.name = new_name.to_string();
+        self.medications.insert(new_name.to_string(), med);
+        self.refresh_display_order();
+
+        for log in self.daily_logs.values_mut() {
+            if let Some(doses) = log.taken.remove(old_name) {
+                log.taken.insert(new_name.to_string(), doses);
+            }
+            if let Some(until) = log.snoozed_until.remove(old_name) {
+                log.snoozed_until.insert(new_name.to_string(), until);
+            }
+            if let Some(shown) = log.reminders_shown.remove(old_name) {
+                log.reminders_shown.insert(new_name.to_string(), shown);
+            }
+            if log.acknowledged.remove(old_name) {
+                log.acknowledged.insert(new_name.to_string());
+            }
+        }
+        for action in self.history.iter_mut().filter(|action| action.med_name == old_name) {
+            action.med_name = new_name.to_string();
+        }
+        if self.groups.values().any(|members| members.iter().any(|member| member == old_name)) {
+            for member in self.groups.values_mut().flatten().filter(|member| *member == old_name) {
+                *member = new_name.to_string();
+            }
+            self.save_groups().map_err(|e| e.to_string())?;
+        }
+
+        self.save_data().map_err(|e| e.to_string())?;
+        self.save_logs().map_err(|e| e.to_string())?;
+        self.audit(&format!("rename_medication,{},{}", escape_csv_field(old_name), escape_csv_field(new_name)))
+            .map_err(|e| e.to_string())
+    }
+
+    fn refresh_display_order(&mut self) {
+        let mut order: Vec<String> = self.medications.keys().cloned().collect();
+        order.sort_by(|a, b| {
+            let (med_a, med_b) = (&self.medications[a], &self.medications[b]);
+            med_a.priority.cmp(&med_b.priority)
+                .then_with(|| schedule_sort_key(&med_a.time_of_day).cmp(&schedule_sort_key(&med_b.time_of_day)))
+                .then_with(|| a.cmp(b))
+        });
+        self.display_order = order;
+    }
+
+    /// Queues an audit line. It reaches the audit file in `save_all`, after the change it records,
+    /// so a crash never leaves an entry for data that was not saved.
+    fn audit(&self, event: &str) -> io::Result<()> {
+        self.pending_audit.borrow_mut().push(format!("{},{}", Local::now().format("%Y-%m-%dT%H:%M:%S"), event));
+        if self.immediate_persistence {
+            self.save_all()?;
+        }
+        Ok(())
+    }
+
+    /// Audit lines on disk followed by any still waiting for the next save.
+    pub fn read_audit(&self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        if self.audit_file.exists() {
+            let reader = BufReader::new(File::open(&self.audit_file)?);
+            lines.extend(reader.lines().map_while(Result::ok));
+        }
+        lines.extend(self.pending_audit.borrow().iter().cloned());
+        Ok(lines)
+    }
+}
+
+impl Drop for MedicationTracker {
+    fn drop(&mut self) {
+        if let Err(e) = self.save_all() {
+            eprintln!("Error saving data for {}: {}", self.patient_name, e);
+        }
+    }
+}
+
+fn log_lines(log: &DailyLog) -> String {
+    let mut lines = String::new();
+    for (med_name, doses) in &log.taken {
+        for (dose, record) in doses {
+            let (status, reason) = match &record.status {
+                DoseStatus::Taken => ("1", ""),
+                DoseStatus::Missed => ("0", ""),
+                DoseStatus::Skipped(reason) => ("S", reason.as_str()),
+            };
+            lines.push_str(&format!("{},{},{},{},{},{},{},{}\n",
+                escape_csv_field(&log.date),
+                escape_csv_field(med_name),
+                status,
+                dose,
+                record.time.as_deref().unwrap_or(""),
+                escape_csv_field(reason),
+                escape_csv_field(record.administered_by.as_deref().unwrap_or("")),
+                if record.deducted { "1" } else { "0" }
+            ));
+        }
+    }
+    lines
+}
+
+fn escape_csv_field(field: &str) -> String {
+    let needs_quotes = field.contains(',')
+        || field.contains('"')
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace);
+
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Appends one content line, folded so no physical line exceeds 75 octets (RFC 5545 3.1).
+/// Continuation lines start with a space and never split a UTF-8 character.
+fn push_ics_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    current.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                current.push(c);
+            }
+        } else if c == '"' {
+            in_quotes = true;
+        } else if c == ',' {
+            fields.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+pub fn adherence_bar(percentage: f32) -> String {
+    const WIDTH: usize = 10;
+    let percentage = percentage.clamp(0.0, 100.0);
+    
+    let mut filled = (percentage / 100.0 * WIDTH as f32).round() as usize;
+    if percentage > 0.0 && percentage < 100.0 {
+        filled = filled.clamp(1, WIDTH - 1);
+    }
+    
+    format!("[{}{}] {:.0}%", "█".repeat(filled), "░".repeat(WIDTH - filled), percentage)
+}
+
+pub fn normalize_date(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    
+    for format in DATE_INPUT_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(input, format) {
+            return Ok(date.format(DATE_FORMAT).to_string());
+        }
+    }
+    Err(format!("Invalid date '{}'. Use YYYY-MM-DD", input))
+}
+
+/// Calendar arithmetic goes through chrono, so month lengths, Feb 29 and year rollovers come out
+/// right. `None` when `date` isn't YYYY-MM-DD or the result is out of range.
+pub fn add_days(date: &str, days: i64) -> Option<String> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT)
+        .ok()
+        .and_then(|parsed| parsed.checked_add_signed(Duration::try_days(days)?))
+        .map(|shifted| shifted.format(DATE_FORMAT).to_string())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn normalize_drug_name(name: &str) -> Option<&'static str> {
+    name.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .find_map(|word| {
+            DRUG_ALIASES.iter()
+                .find(|(alias, _)| *alias == word)
+                .map(|(_, generic)| *generic)
+                .or_else(|| DRUG_INTERACTIONS.iter()
+                    .flat_map(|(a, b, _)| [*a, *b])
+                    .find(|generic| *generic == word))
+        })
+}
+
+pub fn parse_quantity(input: &str) -> Result<u32, String> {
+    let input = input.trim();
+    input.parse()
+        .map_err(|_| format!("'{}' is not a valid quantity. Enter a whole number like 30.", input))
+}
+
+/// Like `parse_quantity`, but blank input means `default` rather than an error.
+pub fn parse_quantity_or(input: &str, default: u32) -> Result<u32, String> {
+    if input.trim().is_empty() {
+        Ok(default)
+    } else {
+        parse_quantity(input)
+    }
+}
+
+pub fn parse_dosage(input: &str) -> Result<Dosage, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    
+    let amount: f32 = amount.parse()
+        .map_err(|_| format!("'{}' does not start with an amount", input))?;
+    if amount <= 0.0 {
+        return Err(format!("Dosage amount must be positive, got {}", amount));
+    }
+    
+    let unit = unit.trim().to_lowercase();
+    if unit.is_empty() {
+        return Err(format!("'{}' is missing a unit (mg, mcg, ml, IU, pill or tablet)", input));
+    }
+    let unit = DOSAGE_UNITS.iter()
+        .find(|(alias, _)| *alias == unit)
+        .map(|(_, canonical)| canonical.to_string())
+        .ok_or_else(|| format!("Unrecognized dosage unit '{}'", unit))?;
+    
+    Ok(Dosage { amount, unit })
+}
+
+pub fn parse_schedule(input: &str) -> Result<Schedule, String> {
+    let input = input.trim();
+    
+    if let Some((hour, minute)) = input.split_once(':') {
+        let hour: u8 = hour.trim().parse().map_err(|_| format!("Invalid hour in '{}'", input))?;
+        let minute: u8 = minute.trim().parse().map_err(|_| format!("Invalid minute in '{}'", input))?;
+        if hour > 23 || minute > 59 {
+            return Err(format!("Time '{}' is out of range (00:00-23:59)", input));
+        }
+        return Ok(Schedule::Clock(hour, minute));
+    }
+    
+    TIME_BUCKETS.iter()
+        .find(|bucket| bucket.eq_ignore_ascii_case(input))
+        .map(|bucket| Schedule::Named(bucket.to_string()))
+        .ok_or_else(|| format!("Unknown time of day '{}'. Use {} or HH:MM", input, TIME_BUCKETS.join(", ")))
+}
+
+fn clock_minutes(time: &str) -> Option<u32> {
+    parse_schedule(time)
+        .ok()
+        .and_then(|schedule| schedule.clock_time())
+        .map(|(hour, minute)| hour as u32 * 60 + minute as u32)
+}
+
+fn default_grace_minutes() -> u32 {
+    DEFAULT_GRACE_MINUTES
+}
+
+fn default_units_per_dose() -> Quantity {
+    Quantity::ONE
+}
+
+/// The "As needed" bucket is how PRN medications are scheduled, so it always implies `is_prn`.
+fn is_as_needed(time_of_day: &str) -> bool {
+    time_of_day.trim().eq_ignore_ascii_case("As needed")
+}
+
+fn time_bucket(time_of_day: &str) -> Option<&'static str> {
+    match parse_schedule(time_of_day).ok()? {
+        Schedule::Named(name) => TIME_BUCKETS.iter().copied().find(|bucket| *bucket == name && *bucket != "As needed"),
+        Schedule::Clock(hour, _) => Some(match hour {
+            0..=11 => "Morning",
+            12..=16 => "Afternoon",
+            17..=20 => "Evening",
+            _ => "Bedtime",
+        }),
+    }
+}
+
+fn in_reminder_window(time_of_day: &str, now: u32) -> bool {
+    let Some(bucket) = time_bucket(time_of_day) else {
+        return true;
+    };
+    REMINDER_WINDOWS.iter()
+        .filter(|(name, _, _)| *name == bucket)
+        .all(|&(_, start, end)| if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        })
+}
+
+fn schedule_sort_key(time_of_day: &str) -> u32 {
+    clock_minutes(time_of_day).unwrap_or(u32::MAX)
+}
+
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    
+    let slug = slug.trim_matches('_');
+    if slug.is_empty() {
+        "patient".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// The file stem for `patient_name` and whether patients.txt already lists it. Nothing is
+/// written here; see `MedicationTracker::register_patient`.
+fn patient_file_stem(dir: &Path, patient_name: &str) -> io::Result<(String, bool)> {
+    let registry = dir.join(PATIENT_REGISTRY_FILE);
+    let mut taken = Vec::new();
+    
+    if registry.exists() {
+        let reader = BufReader::new(File::open(&registry)?);
+        for line in reader.lines().map_while(Result::ok) {
+            let line = line.trim_end_matches('\r').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts = parse_csv_line(line);
+            if parts.len() == 2 {
+                if parts[1] == patient_name {
+                    return Ok((parts[0].clone(), true));
+                }
+                taken.push(parts[0].clone());
+            }
+        }
+    }
+    
+    let base = slugify(patient_name);
+    let mut stem = base.clone();
+    let mut suffix = 2;
+    while taken.contains(&stem) {
+        stem = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    Ok((stem, false))
+}
+
+fn migrate_legacy_files(dir: &Path, patient_name: &str, file_stem: &str) -> io::Result<()> {
+    let path_safe = !patient_name.is_empty()
+        && !patient_name.contains(['/', '\\'])
+        && patient_name != "."
+        && patient_name != "..";
+    if !path_safe || patient_name == file_stem {
+        return Ok(());
+    }
+    
+    for suffix in PATIENT_FILE_SUFFIXES {
+        let legacy = dir.join(format!("{}_{}", patient_name, suffix));
+        let current = dir.join(format!("{}_{}", file_stem, suffix));
+        if legacy.is_file() && !current.exists() {
+            fs::rename(&legacy, &current)?;
+        }
+    }
+    Ok(())
+}
+
+fn default_data_dir(patient_name: &str) -> PathBuf {
+    if let Ok(dir) = env::var("MEDITRACK_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+    
+    if Path::new(&format!("{}_meds.txt", patient_name)).exists() || Path::new(PATIENT_REGISTRY_FILE).exists() {
+        return PathBuf::from(".");
+    }
+    
+    match env::var("HOME").or_else(|_| env::var("USERPROFILE")) {
+        Ok(home) => Path::new(&home).join(".meditrack"),
+        Err(_) => PathBuf::from("."),
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    match (NaiveDate::from_ymd_opt(year, month, 1), NaiveDate::from_ymd_opt(next_year, next_month, 1)) {
+        (Some(first), Some(next)) => next.signed_duration_since(first).num_days() as u32,
+        _ => 0,
+    }
+}
+
+pub fn date_range(start: &str, end: &str) -> Vec<String> {
+    let (Ok(mut current), Ok(end)) = (
+        NaiveDate::parse_from_str(start, DATE_FORMAT),
+        NaiveDate::parse_from_str(end, DATE_FORMAT),
+    ) else {
+        return Vec::new();
+    };
+    
+    let mut dates = Vec::new();
+    while current <= end {
+        dates.push(current.format(DATE_FORMAT).to_string());
+        match current.succ_opt() {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+    dates
+}
+
+pub fn get_today() -> String {
+    Local::now().format(DATE_FORMAT).to_string()
+}
+
+pub fn get_week_start() -> String {
+    let today = Local::now().date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    monday.format(DATE_FORMAT).to_string()
+}
@@ -0,0 +1,1770 @@
+use chrono::{Datelike, Local, Weekday};
+use medication_tracker::{
+    add_days, get_today, normalize_date, parse_dosage, parse_quantity, parse_quantity_or, parse_schedule, tr, ConsoleSink,
+    Locale, MarkOutcome, MedicationTracker, ReminderSink, STALE_AFTER_DAYS, TIME_BUCKETS,
+};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+const MENU_ITEMS: [&str; 42] = [
+    "View Today's Medications",
+    "Mark Medication as Taken",
+    "Mark Medication as Missed",
+    "View All Medications",
+    "Add New Medication",
+    "Refill Medication",
+    "View Weekly Summary",
+    "Save Weekly Report to File",
+    "Export Weekly Report as CSV",
+    "Remove Medication",
+    "Edit Medication",
+    "Switch to JSON Storage",
+    "View Adherence for Date Range",
+    "Undo Last Action",
+    "Save Monthly Report to File",
+    "Search Medications",
+    "Mark ALL as Taken",
+    "Snooze a Reminder",
+    "Export Reminders to Calendar (.ics)",
+    "Switch Patient",
+    "Backup Data",
+    "Restore from Backup",
+    "Lifetime Statistics",
+    "View Recent Activity",
+    "Move Logged Doses to Another Date",
+    "Days with Missed Doses",
+    "Archive Old Logs",
+    "Copy Regimen to Another Patient",
+    "Dismiss a Reminder",
+    "Reset a Day's Log",
+    "Import Medications from CSV",
+    "Adherence by Time of Day",
+    "Log a Dose for a Different Date",
+    "Check & Repair Data",
+    "Pause or Resume a Medication",
+    "Set Default Starting Quantity",
+    "Catch Up on Missed Days",
+    "Save Clinical Report",
+    "Medication Groups",
+    "Refill All",
+    "Set Week Start Day",
+    "Exit",
+];
+
+enum Command {
+    MissedToday,
+    DueSoon(u32),
+    MarkTaken(String),
+    Digest,
+    ExportJsonl,
+}
+
+struct CliArgs {
+    patient: Option<String>,
+    command: Option<Command>,
+    locale: Locale,
+}
+
+fn clear_screen() {
+    print!("\n{}\n", "=".repeat(50));
+}
+
+fn print_header(text: &str) {
+    println!("\n{:=^50}", text);
+}
+
+fn prompt_dose(tracker: &MedicationTracker, med_name: &str) -> Result<u32, InputClosed> {
+    let doses_per_day = tracker.get_medication(med_name)
+        .map(|med| med.doses_per_day)
+        .unwrap_or(1);
+    
+    if doses_per_day <= 1 {
+        return Ok(1);
+    }
+    
+    print!("Which dose (1-{}): ", doses_per_day);
+    io::stdout().flush().unwrap();
+    let input = read_input()?;
+    Ok(input.trim().parse().unwrap_or(0))
+}
+
+fn mark_taken_flow(tracker: &mut MedicationTracker, date: &str) -> Result<(), InputClosed> {
+    let meds = tracker.medication_names();
+    if meds.is_empty() {
+        println!("No medications to mark.");
+        return Ok(());
+    }
+    
+    for (i, med) in meds.iter().enumerate() {
+        println!("{}. {}", i + 1, med);
+    }
+    
+    print!("Enter number or type the name: ");
+    io::stdout().flush().unwrap();
+    let input = read_input()?;
+    
+    let selected = match input.trim().parse::<usize>() {
+        Ok(num) if num > 0 && num <= meds.len() => Ok(meds[num - 1].clone()),
+        Ok(_) => Err("Invalid selection.".to_string()),
+        Err(_) => tracker.resolve_medication(&input),
+    };
+    
+    match selected {
+        Ok(med_name) => {
+            if tracker.get_medication(&med_name).is_some_and(|med| med.current_count < med.units_per_dose) {
+                print!("You're out of {} - refill before marking taken? (y/n): ", med_name);
+                io::stdout().flush().unwrap();
+                let confirm = read_input()?;
+                
+                if confirm.trim().eq_ignore_ascii_case("y") {
+                    print!("Enter refill amount: ");
+                    io::stdout().flush().unwrap();
+                    let amount = read_input()?;
+                    
+                    if let Err(e) = amount.parse().and_then(|amount| tracker.refill_medication(&med_name, amount)) {
+                        println!("Error: {}", e);
+                    }
+                }
+            }
+            
+            let dose = prompt_dose(tracker, &med_name)?;
+            print!("Given by (blank if you took it yourself): ");
+            io::stdout().flush().unwrap();
+            let given_by = read_input()?;
+            
+            match tracker.mark_taken_by(&med_name, date, dose, &given_by) {
+                Ok(MarkOutcome::Recorded) => println!("Recorded: {} dose {} taken", med_name, dose),
+                Ok(MarkOutcome::OutOfStock) => println!("Recorded: {} dose {} taken (supply is empty, please refill)", med_name, dose),
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+        Err(e) => println!("{}", e),
+    }
+    Ok(())
+}
+
+fn time_of_day_choice(input: &str) -> Result<String, String> {
+    match input.trim() {
+        "1" => Ok("Morning".to_string()),
+        "2" => Ok("Afternoon".to_string()),
+        "3" => Ok("Evening".to_string()),
+        "4" => Ok("Bedtime".to_string()),
+        "5" => Ok("As needed".to_string()),
+        other => parse_schedule(other).map(|schedule| schedule.to_string()),
+    }
+}
+
+fn prompt_time_of_day() -> Result<String, InputClosed> {
+    println!("1. Morning");
+    println!("2. Afternoon");
+    println!("3. Evening");
+    println!("4. Bedtime");
+    println!("5. As needed");
+    loop {
+        print!("Select (1-5) or enter a time (HH:MM): ");
+        io::stdout().flush().unwrap();
+        let input = read_input()?;
+        
+        match time_of_day_choice(&input) {
+            Ok(time_of_day) => return Ok(time_of_day),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+fn print_load_warnings(tracker: &MedicationTracker) {
+    let warnings = tracker.load_warnings();
+    if warnings.is_empty() {
+        return;
+    }
+    
+    eprintln!("WARNING: {} record{} could not be loaded - copies were kept for recovery:",
+        warnings.len(), if warnings.len() == 1 { "" } else { "s" });
+    for warning in warnings {
+        eprintln!("   * {}", warning);
+    }
+}
+
+fn wait_for_enter() -> Result<(), InputClosed> {
+    println!("\nPress ENTER to continue...");
+    read_input().map(drop)
+}
+
+fn read_line() -> Option<String> {
+    read_line_from(&mut io::stdin().lock())
+}
+
+fn read_line_from(reader: &mut impl BufRead) -> Option<String> {
+    let mut input = String::new();
+    match reader.read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input),
+    }
+}
+
+/// Input closed partway through a menu flow. Flows pass it up with `?` so the tracker, and the
+/// data lock it holds, are dropped normally on the way out of `main`.
+struct InputClosed;
+
+/// Reads a line for a prompt inside a menu flow.
+fn read_input() -> Result<String, InputClosed> {
+    read_line().ok_or(InputClosed)
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs, String> {
+    let mut cli = CliArgs { patient: None, command: None, locale: Locale::default() };
+    let mut iter = args.iter();
+    
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--patient" => {
+                cli.patient = Some(iter.next().ok_or("--patient requires a name")?.clone());
+            }
+            "--lang" => {
+                cli.locale = iter.next().ok_or("--lang requires a language code")?.parse()?;
+            }
+            "--missed-today" => cli.command = Some(Command::MissedToday),
+            "--due-soon" => {
+                let minutes = iter.next().ok_or("--due-soon requires a number of minutes")?;
+                cli.command = Some(Command::DueSoon(parse_quantity(minutes)?));
+            }
+            "--digest" => cli.command = Some(Command::Digest),
+            "--export-jsonl" => cli.command = Some(Command::ExportJsonl),
+            "--mark-taken" => {
+                let med_name = iter.next().ok_or("--mark-taken requires a medication name")?;
+                cli.command = Some(Command::MarkTaken(med_name.clone()));
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+    Ok(cli)
+}
+
+fn run_command(tracker: &mut MedicationTracker, command: Command, today: &str) -> Result<(), String> {
+    match command {
+        Command::MissedToday => {
+            for reminder in tracker.get_missed_medications(today)? {
+                println!("{}", reminder);
+            }
+            Ok(())
+        }
+        Command::DueSoon(minutes) => {
+            let now = Local::now().format("%H:%M").to_string();
+            for reminder in tracker.due_soon(today, &now, minutes) {
+                println!("{}", reminder);
+            }
+            Ok(())
+        }
+        Command::MarkTaken(med_name) => {
+            let dose = tracker.next_untaken_dose(&med_name, today)?;
+            if tracker.mark_taken(&med_name, today, dose, true)? == MarkOutcome::OutOfStock {
+                eprintln!("Warning: {} is out of stock. Please refill.", med_name);
+            }
+            println!("Recorded: {} dose {} taken", med_name, dose);
+            Ok(())
+        }
+        Command::Digest => {
+            println!("{}", tracker.daily_digest(today)?);
+            Ok(())
+        }
+        Command::ExportJsonl => tracker.export_log_jsonl(&mut io::stdout().lock()).map_err(|e| e.to_string()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli = match parse_args(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: medication_tracker [--patient NAME] [--lang en|es] [--missed-today | --due-soon MINUTES | --digest | --mark-taken MEDICATION | --export-jsonl]");
+            process::exit(2);
+        }
+    };
+    
+    if let Some(command) = cli.command {
+        let Some(patient_name) = cli.patient else {
+            eprintln!("Error: --patient is required with --missed-today, --due-soon, --digest, --mark-taken and --export-jsonl");
+            process::exit(2);
+        };
+        
+        let mut tracker = match MedicationTracker::new(&patient_name) {
+            Ok(tracker) => tracker,
+            Err(e) => {
+                eprintln!("Error loading data for {}: {}", patient_name, e);
+                process::exit(1);
+            }
+        };
+        
+        print_load_warnings(&tracker);
+        tracker.set_locale(cli.locale);
+        if let Err(e) = run_command(&mut tracker, command, &get_today()) {
+            eprintln!("Error: {}", e);
+            drop(tracker);
+            process::exit(1);
+        }
+        if let Err(e) = tracker.save_all() {
+            eprintln!("Error saving data: {}", e);
+            drop(tracker);
+            process::exit(1);
+        }
+        return;
+    }
+    
+    clear_screen();
+    print_header(" MEDICATION TRACKER FOR SENIORS ");
+    
+    let patient_name = match cli.patient {
+        Some(name) => name,
+        None => {
+            println!("Enter patient name: ");
+            let Some(patient_name) = read_line() else {
+                return;
+            };
+            patient_name.trim().to_string()
+        }
+    };
+    
+    let mut tracker = match MedicationTracker::new(&patient_name) {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            println!("Error loading data for {}: {}", patient_name, e);
+            return;
+        }
+    };
+    print_load_warnings(&tracker);
+    let locale = cli.locale;
+    tracker.set_locale(locale);
+    // Input can close mid-flow, so every change goes straight to disk.
+    if let Err(e) = tracker.set_immediate_persistence(true) {
+        println!("Error saving data: {}", e);
+    }
+    if run_menu(&mut tracker, locale).is_err() {
+        println!("\nInput closed. Your data is saved. Goodbye!");
+    }
+}
+
+fn run_menu(tracker: &mut MedicationTracker, locale: Locale) -> Result<(), InputClosed> {
+    let today = get_today();
+    let sink: Box<dyn ReminderSink> = Box::new(ConsoleSink);
+    
+    loop {
+        clear_screen();
+        print_header(&format!(" {}, {} ", tr(locale, "Hello"), tracker.patient_name()));
+        
+        let now = Local::now().format("%H:%M").to_string();
+        if let Err(e) = tracker.record_reminders_shown(&today, &now) {
+            println!("Error saving reminder state: {}", e);
+        }
+        let status = tracker.check_today_status(&today).unwrap_or_default();
+        let overdue_labels = tracker.overdue_medications(&today, &now);
+        let (overdue, due): (Vec<_>, Vec<_>) = tracker.active_reminder_entries(&today, &now)
+            .into_iter()
+            .partition(|(_, message)| overdue_labels.contains(message));
+        let (overdue, due) = (tracker.group_reminder_entries(overdue), tracker.group_reminder_entries(due));
+        let upcoming = tracker.upcoming_medications(&today, &now);
+        
+        println!("{}: {}", tr(locale, "TODAY"), today);
+        println!("{}", "-".repeat(50));
+        
+        if !overdue.is_empty() {
+            println!("{}", tr(locale, "OVERDUE - Please take as soon as possible:"));
+            for (med, message) in &overdue {
+                sink.notify(med, message);
+            }
+        }
+        if !due.is_empty() {
+            println!("{}", tr(locale, "DUE NOW - Please take:"));
+            for (med, message) in &due {
+                sink.notify(med, message);
+            }
+        }
+        if let Some((name, time)) = tracker.next_dose(&today, &now) {
+            println!("{}: {}", tr(locale, "Next"), tr(locale, "{med} at {time}").replace("{med}", &name).replace("{time}", &time));
+        }
+        if !upcoming.is_empty() {
+            println!("{}", tr(locale, "Coming up later today:"));
+            for reminder in &upcoming {
+                println!("   * {}", reminder);
+            }
+        }
+        let (as_needed, scheduled): (Vec<_>, Vec<_>) = status.iter()
+            .partition(|entry| tracker.get_medication(&entry.0).is_some_and(|med| med.is_prn));
+        if scheduled.is_empty() {
+            println!("{}", tr(locale, "No medications scheduled."));
+        } else if scheduled.iter().all(|entry| entry.2) {
+            println!("{}", tr(locale, "All medications taken today!"));
+        }
+        if !as_needed.is_empty() {
+            println!("{}:", tr(locale, "As needed"));
+            for (name, _, _, reminder) in as_needed {
+                println!("   * {} ({})", name, reminder);
+            }
+        }
+        
+        let streak = tracker.current_streak(&today).unwrap_or_default();
+        if streak > 0 {
+            println!("Current streak: {} day{}!", streak, if streak == 1 { "" } else { "s" });
+        }
+        
+        let week_start = tracker.week_start(&today);
+        let trend = tracker.adherence_trend(2, &week_start);
+        if let [(last_week, previous), (_, current)] = trend.as_slice() {
+            if tracker.week_has_logs(&week_start) {
+                if tracker.week_has_logs(last_week) {
+                    let arrow = if current > previous {
+                        "↑"
+                    } else if current < previous {
+                        "↓"
+                    } else {
+                        "→"
+                    };
+                    println!("Adherence this week {:.0}% ({} from {:.0}%)", current, arrow, previous);
+                } else {
+                    println!("Adherence this week {:.0}% (no data last week)", current);
+                }
+            }
+        }
+        
+        for (med_a, med_b, warning) in tracker.check_interactions() {
+            println!("INTERACTION WARNING: {} + {} - {}", med_a, med_b, warning);
+        }
+        
+        let supply = tracker.supply_warnings();
+        if !supply.is_empty() {
+            println!("LOW STOCK: {} medication{} running low", supply.len(), if supply.len() == 1 { " is" } else { "s are" });
+            for warning in supply.iter().take(3) {
+                println!("   * {}", warning);
+            }
+            if supply.len() > 3 {
+                println!("   ...and {} more", supply.len() - 3);
+            }
+        }
+        for warning in tracker.refill_warnings() {
+            println!("REFILL: {}", warning);
+        }
+        for med_name in tracker.stale_medications(&today, STALE_AFTER_DAYS) {
+            println!("No activity for {} in {} days — still taking it?", med_name, STALE_AFTER_DAYS);
+        }
+        for med_name in tracker.medication_names() {
+            if let Some(time) = tracker.suggested_time(&med_name) {
+                println!("You usually take {} around {} — update schedule?", med_name, time);
+            }
+        }
+        for (med_name, end) in tracker.finished_courses(&today) {
+            println!("{}'s course ended on {} — remove it with Remove Medication?", med_name, end);
+        }
+        
+        println!("{}", "-".repeat(50));
+        println!("{}", tr(locale, "MENU:"));
+        for (i, item) in MENU_ITEMS.iter().enumerate() {
+            println!("{}. {}", i + 1, tr(locale, item));
+        }
+        println!("{}", "-".repeat(50));
+        print!("{} (1-{}): ", tr(locale, "Choice"), MENU_ITEMS.len());
+        
+        io::stdout().flush().unwrap();
+        let choice = read_input()?;
+        
+        match choice.trim() {
+            "1" => {
+                clear_screen();
+                print_header(" TODAY'S MEDICATIONS ");
+                
+                if status.is_empty() {
+                    println!("No medications scheduled.");
+                } else {
+                    for (name, details, taken, reminder) in status {
+                        let status_symbol = if taken { "[X] TAKEN" } else { "[ ] NOT TAKEN" };
+                        println!("{}", name);
+                        println!("   Status: {}", status_symbol);
+                        println!("   Details: {}", details);
+                        if !taken {
+                            println!("   *** {}", reminder);
+                        }
+                        println!();
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "2" => {
+                clear_screen();
+                print_header(" MARK AS TAKEN ");
+                
+                mark_taken_flow(tracker, &today)?;
+                wait_for_enter()?;
+            }
+            
+            "3" => {
+                clear_screen();
+                print_header(" MARK AS MISSED ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications to mark.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        let dose = prompt_dose(tracker, med_name)?;
+                        
+                        print!("Reason if skipped on purpose (leave blank if missed): ");
+                        io::stdout().flush().unwrap();
+                        let reason = read_input()?;
+                        
+                        if reason.trim().is_empty() {
+                            match tracker.mark_taken(med_name, &today, dose, false) {
+                                Ok(_) => println!("Recorded: {} dose {} missed", med_name, dose),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        } else {
+                            match tracker.skip_dose(med_name, &today, dose, &reason) {
+                                Ok(_) => println!("Recorded: {} dose {} skipped ({})", med_name, dose, reason.trim()),
+                                Err(e) => println!("Error: {}", e),
+                            }
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "4" => {
+                clear_screen();
+                print_header(" ALL MEDICATIONS ");
+                
+                let meds = tracker.list_medications(&today);
+                let paused = tracker.paused_medications();
+                if meds.is_empty() && paused.is_empty() {
+                    println!("No medications on record.");
+                } else {
+                    for med in meds {
+                        println!("* {}", med);
+                    }
+                    if !paused.is_empty() {
+                        println!("\nPaused:");
+                        for med in paused {
+                            println!("* {}", med);
+                        }
+                    }
+                    println!("\nEstimated monthly cost: ${:.2}", tracker.estimated_monthly_cost());
+                    let unpriced = tracker.unpriced_medications();
+                    if !unpriced.is_empty() {
+                        println!("No cost set for: {} (counted as $0.00)", unpriced.join(", "));
+                    }
+                    let unrecognized = tracker.unrecognized_dosages();
+                    if !unrecognized.is_empty() {
+                        println!("Dosage saved as free text for: {}", unrecognized.join(", "));
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "5" => {
+                clear_screen();
+                print_header(" ADD NEW MEDICATION ");
+                
+                let mut name = String::new();
+                while name.trim().is_empty() {
+                    print!("Medication name: ");
+                    io::stdout().flush().unwrap();
+                    name = read_input()?;
+                    if name.trim().is_empty() {
+                        println!("Medication name cannot be empty.");
+                    }
+                }
+                
+                let mut dosage = String::new();
+                while dosage.trim().is_empty() {
+                    print!("Dosage (e.g., '1 pill', '5ml'): ");
+                    io::stdout().flush().unwrap();
+                    dosage = read_input()?;
+                    if dosage.trim().is_empty() {
+                        println!("Dosage cannot be empty.");
+                    }
+                }
+                if let Err(e) = parse_dosage(&dosage) {
+                    println!("Note: {}. The dosage will be saved as free text.", e);
+                }
+                
+                println!("Time of day:");
+                let time_of_day = prompt_time_of_day()?;
+                
+                print!("Doses per day [1]: ");
+                io::stdout().flush().unwrap();
+                let doses_per_day = read_input()?;
+                
+                let count = loop {
+                    print!("Starting quantity [default {}]: ", tracker.default_quantity());
+                    io::stdout().flush().unwrap();
+                    let input = read_input()?;
+                    match parse_quantity_or(&input, tracker.default_quantity()) {
+                        Ok(count) => break count,
+                        Err(e) => println!("Error: {}", e),
+                    }
+                };
+                
+                print!("Notes (e.g., 'take with food', optional): ");
+                io::stdout().flush().unwrap();
+                let notes = read_input()?;
+                
+                match tracker.add_medication(
+                    name.trim().to_string(),
+                    dosage.trim().to_string(),
+                    time_of_day,
+                    count,
+                    doses_per_day.trim().parse().unwrap_or(1),
+                    notes.trim().to_string(),
+                ) {
+                    Ok(_) => println!("Medication added!"),
+                    Err(e) => {
+                        println!("Error: {}", e);
+                        wait_for_enter()?;
+                        continue;
+                    }
+                }
+                
+                print!("Maximum supply to keep on hand (optional): ");
+                io::stdout().flush().unwrap();
+                let max_count = read_input()?;
+                
+                if !max_count.trim().is_empty() {
+                    match parse_quantity(&max_count) {
+                        Ok(max) => {
+                            if let Err(e) = tracker.set_max_count(name.trim(), Some(max)) {
+                                println!("Error: {}", e);
+                            }
+                        }
+                        Err(e) => println!("Error: {} No maximum was set.", e),
+                    }
+                }
+                
+                // Choosing "As needed" already made it PRN.
+                if tracker.get_medication(name.trim()).is_some_and(|med| !med.is_prn) {
+                    print!("Take only as needed (PRN)? (y/n) [n]: ");
+                    io::stdout().flush().unwrap();
+                    let prn = read_input()?;
+                    
+                    if prn.trim().eq_ignore_ascii_case("y") {
+                        if let Err(e) = tracker.set_prn(name.trim(), true) {
+                            println!("Error: {}", e);
+                        }
+                    }
+                }
+                
+                print!("Priority (High/Normal/Low) [Normal]: ");
+                io::stdout().flush().unwrap();
+                let priority = read_input()?;
+                
+                if !priority.trim().is_empty() {
+                    match priority.parse() {
+                        Ok(priority) => {
+                            if let Err(e) = tracker.set_priority(name.trim(), priority) {
+                                println!("Error: {}", e);
+                            }
+                        }
+                        Err(e) => println!("Error: {}. Priority left as Normal.", e),
+                    }
+                }
+                
+                print!("Frequency (Daily, days like 'Mon Thu', or 'every N days') [Daily]: ");
+                io::stdout().flush().unwrap();
+                let frequency = read_input()?;
+                
+                if !frequency.trim().is_empty() {
+                    match frequency.parse() {
+                        Ok(frequency) => {
+                            if let Err(e) = tracker.set_frequency(name.trim(), frequency) {
+                                println!("Error: {}", e);
+                            }
+                        }
+                        Err(e) => println!("Error: {}. Frequency left as Daily.", e),
+                    }
+                }
+                
+                print!("Cost per pill or unit (optional, e.g. 0.25): ");
+                io::stdout().flush().unwrap();
+                let cost = read_input()?;
+                
+                if !cost.trim().is_empty() {
+                    match cost.trim().trim_start_matches('$').parse::<f32>() {
+                        Ok(cost) => {
+                            if let Err(e) = tracker.set_cost_per_unit(name.trim(), Some(cost)) {
+                                println!("Error: {}", e);
+                            }
+                        }
+                        Err(_) => println!("Invalid cost. No cost was set."),
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "6" => {
+                clear_screen();
+                print_header(" REFILL MEDICATION ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications to refill.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        
+                        print!("Amount to add: ");
+                        io::stdout().flush().unwrap();
+                        let amount = read_input()?;
+                        
+                        match tracker.refill_medication(med_name, amount.trim().parse().unwrap_or_default()) {
+                            Ok(_) => match tracker.refills_left(med_name) {
+                                Ok(left) => println!("{} refilled! {} refill{} left on the prescription.", med_name, left, if left == 1 { "" } else { "s" }),
+                                Err(_) => println!("{} refilled!", med_name),
+                            },
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "7" => {
+                clear_screen();
+                print_header(" WEEKLY SUMMARY ");
+                
+                let week_start = tracker.week_start(&get_today());
+                match tracker.generate_weekly_summary(&week_start) {
+                    Ok(summary) => println!("{}", summary),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "8" => {
+                clear_screen();
+                print_header(" SAVE WEEKLY REPORT ");
+                
+                let week_start = tracker.week_start(&get_today());
+                let mut overwrite = false;
+                if let Ok(path) = tracker.weekly_report_path(&week_start) {
+                    if path.exists() {
+                        print!("{} already exists. Overwrite? (y/n): ", path.display());
+                        io::stdout().flush().unwrap();
+                        let confirm = read_input()?;
+                        
+                        if !confirm.trim().eq_ignore_ascii_case("y") {
+                            println!("Report not saved.");
+                            wait_for_enter()?;
+                            continue;
+                        }
+                        overwrite = true;
+                    }
+                }
+                
+                match tracker.save_chart_to_file(&week_start, overwrite) {
+                    Ok(filename) => println!("Report saved to: {}", filename),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "9" => {
+                clear_screen();
+                print_header(" EXPORT WEEKLY CSV ");
+                
+                let week_start = tracker.week_start(&get_today());
+                match tracker.export_weekly_csv(&week_start) {
+                    Ok(filename) => println!("CSV saved to: {}", filename),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "10" => {
+                clear_screen();
+                print_header(" REMOVE MEDICATION ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications to remove.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        match tracker.remove_medication(med_name) {
+                            Ok(_) => println!("{} removed.", med_name),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "11" => {
+                clear_screen();
+                print_header(" EDIT MEDICATION ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications to edit.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        
+                        println!("Change:");
+                        println!("1. Dosage");
+                        println!("2. Time of day");
+                        println!("3. Priority");
+                        println!("4. Frequency");
+                        println!("5. Cost per unit");
+                        println!("6. Reminder grace period");
+                        println!("7. Name");
+                        println!("8. Amount per dose");
+                        println!("9. Prescription refills");
+                        println!("10. Course start/end dates");
+                        print!("Select (1-10): ");
+                        io::stdout().flush().unwrap();
+                        let field = read_input()?;
+                        
+                        let result = match field.trim() {
+                            "1" => {
+                                print!("New dosage: ");
+                                io::stdout().flush().unwrap();
+                                let dosage = read_input()?;
+                                tracker.edit_medication(med_name, Some(dosage.trim().to_string()), None)
+                            }
+                            "2" => {
+                                if let Some(time) = tracker.suggested_time(med_name) {
+                                    println!("You usually take it around {}.", time);
+                                }
+                                println!("New time of day:");
+                                let time_of_day = prompt_time_of_day()?;
+                                tracker.edit_medication(med_name, None, Some(time_of_day))
+                            }
+                            "3" => {
+                                print!("New priority (High/Normal/Low): ");
+                                io::stdout().flush().unwrap();
+                                let priority = read_input()?;
+                                priority.parse().and_then(|priority| tracker.set_priority(med_name, priority))
+                            }
+                            "4" => {
+                                print!("New frequency (Daily, days like 'Mon Thu', or 'every N days'): ");
+                                io::stdout().flush().unwrap();
+                                let frequency = read_input()?;
+                                frequency.parse().and_then(|frequency| tracker.set_frequency(med_name, frequency))
+                            }
+                            "5" => {
+                                print!("New cost per pill or unit (blank to clear): ");
+                                io::stdout().flush().unwrap();
+                                let cost = read_input()?;
+                                
+                                if cost.trim().is_empty() {
+                                    tracker.set_cost_per_unit(med_name, None)
+                                } else {
+                                    cost.trim().trim_start_matches('$').parse::<f32>()
+                                        .map_err(|_| "Invalid cost".to_string())
+                                        .and_then(|cost| tracker.set_cost_per_unit(med_name, Some(cost)))
+                                }
+                            }
+                            "6" => {
+                                print!("Minutes after the scheduled time before a dose is overdue: ");
+                                io::stdout().flush().unwrap();
+                                let minutes = read_input()?;
+                                parse_quantity(&minutes).and_then(|minutes| tracker.set_grace_minutes(med_name, minutes))
+                            }
+                            "7" => {
+                                print!("New name: ");
+                                io::stdout().flush().unwrap();
+                                let new_name = read_input()?;
+                                tracker.rename_medication(med_name, &new_name)
+                            }
+                            "8" => {
+                                print!("Pills per dose (e.g. 0.5 for half a tablet): ");
+                                io::stdout().flush().unwrap();
+                                let amount = read_input()?;
+                                amount.parse().and_then(|amount| tracker.set_units_per_dose(med_name, amount))
+                            }
+                            "9" => {
+                                print!("Pills per refill (0 to stop tracking refills): ");
+                                io::stdout().flush().unwrap();
+                                let size = read_input()?;
+                                print!("Refills remaining: ");
+                                io::stdout().flush().unwrap();
+                                let remaining = read_input()?;
+                                parse_quantity(&size)
+                                    .and_then(|size| Ok((size, parse_quantity(&remaining)?)))
+                                    .and_then(|(size, remaining)| tracker.set_refills(med_name, size, remaining))
+                            }
+                            "10" => {
+                                print!("Start date (YYYY-MM-DD, blank for none): ");
+                                io::stdout().flush().unwrap();
+                                let start = read_input()?;
+                                print!("End date (YYYY-MM-DD, blank for none): ");
+                                io::stdout().flush().unwrap();
+                                let end = read_input()?;
+                                tracker.set_course_dates(med_name, &start, &end)
+                            }
+                            _ => Err("Invalid selection.".to_string()),
+                        };
+                        
+                        match result {
+                            Ok(_) => println!("{} updated!", med_name),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "12" => {
+                clear_screen();
+                print_header(" SWITCH TO JSON STORAGE ");
+                
+                if tracker.uses_json_storage() {
+                    println!("Already using JSON storage.");
+                } else {
+                    match tracker.enable_json_storage() {
+                        Ok(_) => println!("Data migrated to: {}", tracker.json_file().display()),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "13" => {
+                clear_screen();
+                print_header(" ADHERENCE FOR DATE RANGE ");
+                
+                print!("Start date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let start = read_input()?;
+                
+                print!("End date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let end = read_input()?;
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications on record.");
+                }
+                
+                for med_name in meds {
+                    match tracker.adherence_rate(&med_name, start.trim(), end.trim()) {
+                        Ok(rate) => println!("* {}: {:.1}%", med_name, rate),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                
+                let ranking = tracker.adherence_ranking(start.trim(), end.trim());
+                if let (Some((best, best_rate)), Some((worst, worst_rate))) = (ranking.first(), ranking.last()) {
+                    if ranking.len() > 1 {
+                        println!("\nBest: {} ({:.0}%), Worst: {} ({:.0}%)", best, best_rate, worst, worst_rate);
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "14" => {
+                clear_screen();
+                print_header(" UNDO LAST ACTION ");
+                
+                match tracker.undo_last() {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "15" => {
+                clear_screen();
+                print_header(" SAVE MONTHLY REPORT ");
+                
+                let now = Local::now();
+                match tracker.save_monthly_report_to_file(now.year(), now.month()) {
+                    Ok(filename) => println!("Report saved to: {}", filename),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "16" => {
+                clear_screen();
+                print_header(" SEARCH MEDICATIONS ");
+                
+                println!("1. Search by name or time");
+                println!("2. Filter by time of day");
+                print!("Select (1-2): ");
+                io::stdout().flush().unwrap();
+                let mode = read_input()?;
+                
+                let results = match mode.trim() {
+                    "1" => {
+                        print!("Search for: ");
+                        io::stdout().flush().unwrap();
+                        let query = read_input()?;
+                        tracker.find_medications(&query)
+                    }
+                    "2" => {
+                        print!("Time of day ({} or HH:MM): ", TIME_BUCKETS.join(", "));
+                        io::stdout().flush().unwrap();
+                        let time = read_input()?;
+                        tracker.medications_at_time(&time)
+                    }
+                    _ => {
+                        println!("Invalid selection.");
+                        wait_for_enter()?;
+                        continue;
+                    }
+                };
+                
+                if results.is_empty() {
+                    println!("No matching medications.");
+                } else {
+                    for med in results {
+                        println!("* {} - {} at {} ({} left)", med.name, med.dosage, med.time_of_day, med.current_count);
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "17" => {
+                clear_screen();
+                print_header(" MARK ALL AS TAKEN ");
+                
+                match tracker.mark_all_taken(&today) {
+                    Ok(0) => println!("Everything was already marked taken today."),
+                    Ok(count) => println!("Recorded {} dose(s) as taken.", count),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "18" => {
+                clear_screen();
+                print_header(" SNOOZE A REMINDER ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications to snooze.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        
+                        print!("Remind me again at (HH:MM): ");
+                        io::stdout().flush().unwrap();
+                        let until = read_input()?;
+                        
+                        match tracker.snooze_reminder(med_name, &today, &until) {
+                            Ok(_) => println!("{} snoozed until {}", med_name, until.trim()),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "19" => {
+                clear_screen();
+                print_header(" EXPORT CALENDAR ");
+                
+                print!("Number of days to include [30]: ");
+                io::stdout().flush().unwrap();
+                let days = read_input()?;
+                
+                match tracker.export_ics(&today, days.trim().parse().unwrap_or(30)) {
+                    Ok(filename) => println!("Calendar saved to: {}", filename),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "20" => {
+                clear_screen();
+                print_header(" SWITCH PATIENT ");
+                
+                println!("Currently tracking: {}", tracker.patient_name());
+                print!("Enter patient name: ");
+                io::stdout().flush().unwrap();
+                let name = read_input()?;
+                
+                match tracker.switch_patient(&name) {
+                    Ok(_) => {
+                        println!("Now tracking: {}", tracker.patient_name());
+                        print_load_warnings(tracker);
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "21" => {
+                clear_screen();
+                print_header(" BACKUP DATA ");
+                
+                let default_dir = tracker.data_dir().join("backups");
+                print!("Backup folder [{}]: ", default_dir.display());
+                io::stdout().flush().unwrap();
+                let dest = read_input()?;
+                
+                let dest = if dest.trim().is_empty() {
+                    default_dir
+                } else {
+                    PathBuf::from(dest.trim())
+                };
+                
+                match tracker.backup(&dest) {
+                    Ok(path) => println!("Backup saved to: {}", path.display()),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "22" => {
+                clear_screen();
+                print_header(" RESTORE FROM BACKUP ");
+                
+                print!("Backup folder to restore: ");
+                io::stdout().flush().unwrap();
+                let source = read_input()?;
+                
+                print!("This will replace current data for {}. Continue? (y/n): ", tracker.patient_name());
+                io::stdout().flush().unwrap();
+                let confirm = read_input()?;
+                
+                if confirm.trim().eq_ignore_ascii_case("y") {
+                    match tracker.restore(Path::new(source.trim())) {
+                        Ok(_) => println!("Data restored from: {}", source.trim()),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                } else {
+                    println!("Restore cancelled.");
+                }
+                wait_for_enter()?;
+            }
+            
+            "23" => {
+                clear_screen();
+                print_header(" LIFETIME STATISTICS ");
+                
+                let stats = tracker.lifetime_stats();
+                if stats.is_empty() {
+                    println!("No doses logged yet.");
+                }
+                
+                let mut names: Vec<&String> = stats.keys().collect();
+                names.sort();
+                
+                for name in names {
+                    let (taken, missed) = stats[name];
+                    let total = taken + missed;
+                    let percentage = if total == 0 { 0.0 } else { taken as f32 / total as f32 * 100.0 };
+                    println!("* {}: {} taken, {} missed ({:.1}%)", name, taken, missed, percentage);
+                }
+                wait_for_enter()?;
+            }
+            
+            "24" => {
+                clear_screen();
+                print_header(" RECENT ACTIVITY ");
+                
+                match tracker.read_audit() {
+                    Ok(lines) if lines.is_empty() => println!("No activity recorded yet."),
+                    Ok(lines) => {
+                        for line in lines.iter().skip(lines.len().saturating_sub(20)) {
+                            println!("* {}", line);
+                        }
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "25" => {
+                clear_screen();
+                print_header(" MOVE LOGGED DOSES ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications on record.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        
+                        print!("Move doses logged on (YYYY-MM-DD): ");
+                        io::stdout().flush().unwrap();
+                        let from = read_input()?;
+                        
+                        print!("To date (YYYY-MM-DD): ");
+                        io::stdout().flush().unwrap();
+                        let to = read_input()?;
+                        
+                        match tracker.relog_dose(med_name, &from, &to) {
+                            Ok(_) => println!("Moved {} from {} to {}", med_name, from.trim(), to.trim()),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "26" => {
+                clear_screen();
+                print_header(" DAYS WITH MISSED DOSES ");
+                
+                print!("Start date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let start = read_input()?;
+                
+                print!("End date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let end = read_input()?;
+                
+                let bad_days = tracker.days_with_misses(&start, &end);
+                if bad_days.is_empty() {
+                    println!("No missed doses in that range.");
+                }
+                for (date, missed) in bad_days {
+                    println!("* {}: {}", date, missed.join(", "));
+                }
+                wait_for_enter()?;
+            }
+            
+            "27" => {
+                clear_screen();
+                print_header(" ARCHIVE OLD LOGS ");
+                
+                print!("Archive entries before (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let cutoff = read_input()?;
+                
+                match tracker.archive_logs_before(cutoff.trim()) {
+                    Ok(0) => println!("No entries older than {}.", cutoff.trim()),
+                    Ok(count) => println!("Archived {} log entries.", count),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "28" => {
+                clear_screen();
+                print_header(" COPY REGIMEN TO ANOTHER PATIENT ");
+                
+                if tracker.medication_names().is_empty() {
+                    println!("No medications to copy.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                print!("Copy {}'s medications to patient: ", tracker.patient_name());
+                io::stdout().flush().unwrap();
+                let name = read_input()?;
+                
+                match tracker.copy_regimen_to(&name) {
+                    Ok(copy) => {
+                        println!("Copied {} medications to {}.", copy.medication_names().len(), copy.patient_name());
+                        print!("Switch to {} now? (y/n): ", copy.patient_name());
+                        io::stdout().flush().unwrap();
+                        let confirm = read_input()?;
+                        
+                        if confirm.trim().eq_ignore_ascii_case("y") {
+                            *tracker = copy;
+                            println!("Now tracking: {}", tracker.patient_name());
+                        }
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "29" => {
+                clear_screen();
+                print_header(" DISMISS A REMINDER ");
+                
+                let meds: Vec<String> = tracker.medication_names()
+                    .into_iter()
+                    .filter(|name| tracker.reminder_level(name, &today) > 0)
+                    .collect();
+                if meds.is_empty() {
+                    println!("No active reminders.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    println!("{}. {}", i + 1, med);
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        match tracker.acknowledge_reminder(&meds[num - 1], &today) {
+                            Ok(_) => println!("Reminder for {} dismissed. It still counts as not taken.", meds[num - 1]),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "30" => {
+                clear_screen();
+                print_header(" RESET A DAY ");
+                
+                print!("Date to clear (YYYY-MM-DD) [{}]: ", today);
+                io::stdout().flush().unwrap();
+                let date = read_input()?;
+                let date = if date.trim().is_empty() { today.clone() } else { date.trim().to_string() };
+                
+                print!("Clear every dose logged on {} and restore pill counts? (y/n): ", date);
+                io::stdout().flush().unwrap();
+                let confirm = read_input()?;
+                
+                if confirm.trim().eq_ignore_ascii_case("y") {
+                    match tracker.reset_day(&date) {
+                        Ok(count) => println!("Cleared {} entries for {}.", count, date),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                } else {
+                    println!("Nothing was changed.");
+                }
+                wait_for_enter()?;
+            }
+            
+            "31" => {
+                clear_screen();
+                print_header(" IMPORT MEDICATIONS FROM CSV ");
+                
+                println!("The file must start with the header: name,dosage,time_of_day,count");
+                print!("Path to CSV file: ");
+                io::stdout().flush().unwrap();
+                let path = read_input()?;
+                
+                match tracker.import_medications_csv(Path::new(path.trim())) {
+                    Ok(count) => {
+                        println!("Imported {} medication{}.", count, if count == 1 { "" } else { "s" });
+                        for error in tracker.import_errors() {
+                            println!("Skipped {}", error);
+                        }
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "32" => {
+                clear_screen();
+                print_header(" ADHERENCE BY TIME OF DAY ");
+                
+                print!("Start date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let start = read_input()?;
+                
+                print!("End date (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let end = read_input()?;
+                
+                let by_bucket = tracker.adherence_by_time_of_day(&start, &end);
+                let breakdown: Vec<String> = TIME_BUCKETS.iter()
+                    .filter_map(|bucket| by_bucket.get(*bucket).map(|rate| format!("{}: {:.0}%", bucket, rate)))
+                    .collect();
+                if breakdown.is_empty() {
+                    println!("No scheduled medications in that range.");
+                } else {
+                    println!("{}", breakdown.join(", "));
+                }
+                wait_for_enter()?;
+            }
+            
+            "33" => {
+                clear_screen();
+                print_header(" LOG FOR A DIFFERENT DATE ");
+                
+                print!("Date the dose was taken (YYYY-MM-DD): ");
+                io::stdout().flush().unwrap();
+                let date = read_input()?;
+                
+                match normalize_date(&date) {
+                    Ok(date) if date > today => println!("Error: {} is in the future.", date),
+                    Ok(date) => {
+                        println!("Logging for {}", date);
+                        mark_taken_flow(tracker, &date)?;
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "34" => {
+                clear_screen();
+                print_header(" CHECK & REPAIR DATA ");
+                
+                let problems = tracker.verify_integrity();
+                if problems.is_empty() {
+                    println!("No problems found.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                println!("Found {} problem{}:", problems.len(), if problems.len() == 1 { "" } else { "s" });
+                for problem in &problems {
+                    println!("   * {}", problem);
+                }
+                
+                print!("Repair now? (y/n): ");
+                io::stdout().flush().unwrap();
+                let confirm = read_input()?;
+                
+                if confirm.trim().eq_ignore_ascii_case("y") {
+                    match tracker.repair() {
+                        Ok(fixes) => {
+                            println!("Applied {} fix{}.", fixes, if fixes == 1 { "" } else { "es" });
+                            for problem in tracker.verify_integrity() {
+                                println!("Still needs attention: {}", problem);
+                            }
+                        }
+                        Err(e) => println!("Error saving repairs: {}", e),
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "35" => {
+                clear_screen();
+                print_header(" PAUSE OR RESUME A MEDICATION ");
+                
+                let meds = tracker.medication_names();
+                if meds.is_empty() {
+                    println!("No medications on record.");
+                    wait_for_enter()?;
+                    continue;
+                }
+                
+                for (i, med) in meds.iter().enumerate() {
+                    let paused = tracker.get_medication(med).is_some_and(|med| med.paused);
+                    println!("{}. {}{}", i + 1, med, if paused { " (paused)" } else { "" });
+                }
+                
+                print!("Enter number: ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if let Ok(num) = input.trim().parse::<usize>() {
+                    if num > 0 && num <= meds.len() {
+                        let med_name = &meds[num - 1];
+                        let paused = !tracker.get_medication(med_name).is_some_and(|med| med.paused);
+                        match tracker.set_paused(med_name, paused) {
+                            Ok(_) if paused => println!("{} paused. No reminders until it is resumed.", med_name),
+                            Ok(_) => println!("{} resumed.", med_name),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Invalid selection.");
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "36" => {
+                clear_screen();
+                print_header(" DEFAULT STARTING QUANTITY ");
+                
+                println!("Current default: {}", tracker.default_quantity());
+                print!("New default (blank to keep): ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if !input.trim().is_empty() {
+                    match parse_quantity(&input).and_then(|quantity| tracker.set_default_quantity(quantity)) {
+                        Ok(_) => println!("New medications will start with {} doses unless you enter a number.", tracker.default_quantity()),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "37" => {
+                clear_screen();
+                print_header(" CATCH UP ON MISSED DAYS ");
+                
+                print!("How many days back to check [default 7]: ");
+                io::stdout().flush().unwrap();
+                let days: i64 = read_input()?.trim().parse().unwrap_or(7);
+                let today = get_today();
+                let (Some(since), Some(until)) = (add_days(&today, -days.max(1)), add_days(&today, -1)) else {
+                    println!("Error: {} days back is out of range", days);
+                    wait_for_enter()?;
+                    continue;
+                };
+                let gaps = tracker.unlogged_dates(&since, &until);
+                
+                if gaps.is_empty() {
+                    println!("Every day in that range has been recorded.");
+                } else {
+                    println!("{} day(s) with nothing recorded. Answer y/n for each dose, or leave blank to skip.", gaps.len());
+                    let mut recorded = 0;
+                    
+                    for date in &gaps {
+                        println!("\n{}:", date);
+                        for med_name in tracker.scheduled_on(date) {
+                            let doses_per_day = tracker.get_medication(&med_name)
+                                .map(|med| med.doses_per_day)
+                                .unwrap_or(1);
+                            
+                            for dose in 1..=doses_per_day {
+                                print!("  {} dose {} taken? ", med_name, dose);
+                                io::stdout().flush().unwrap();
+                                let answer = read_input()?;
+                                let taken = match answer.trim().to_lowercase().as_str() {
+                                    "y" => true,
+                                    "n" => false,
+                                    _ => continue,
+                                };
+                                match tracker.mark_taken(&med_name, date, dose, taken) {
+                                    Ok(_) => recorded += 1,
+                                    Err(e) => println!("  Error: {}", e),
+                                }
+                            }
+                        }
+                    }
+                    println!("\nRecorded {} dose(s).", recorded);
+                }
+                wait_for_enter()?;
+            }
+            
+            "38" => {
+                clear_screen();
+                print_header(" SAVE CLINICAL REPORT ");
+                
+                let today = get_today();
+                let default_start = add_days(&today, -30).unwrap_or_else(|| today.clone());
+                print!("Start date (YYYY-MM-DD) [default {}]: ", default_start);
+                io::stdout().flush().unwrap();
+                let start = read_input()?;
+                let start = if start.trim().is_empty() { default_start } else { start.trim().to_string() };
+                
+                print!("End date (YYYY-MM-DD) [default {}]: ", today);
+                io::stdout().flush().unwrap();
+                let end = read_input()?;
+                let end = if end.trim().is_empty() { today } else { end.trim().to_string() };
+                
+                println!("{}", tracker.generate_clinical_report(&start, &end));
+                match tracker.save_clinical_report_to_file(&start, &end) {
+                    Ok(filename) => println!("Report saved to: {}", filename),
+                    Err(e) => println!("Error: {}", e),
+                }
+                wait_for_enter()?;
+            }
+            
+            "39" => {
+                clear_screen();
+                print_header(" MEDICATION GROUPS ");
+                
+                let groups = tracker.groups();
+                if groups.is_empty() {
+                    println!("No groups yet.");
+                }
+                for (i, (group, members)) in groups.iter().enumerate() {
+                    println!("{}. {}: {}", i + 1, group, members.join(", "));
+                }
+                
+                println!("\n1. Mark a group taken");
+                println!("2. Create a group");
+                println!("3. Remove a group");
+                print!("Select (1-3): ");
+                io::stdout().flush().unwrap();
+                let action = read_input()?;
+                
+                match action.trim() {
+                    "1" | "3" if groups.is_empty() => println!("No groups yet."),
+                    "1" | "3" => {
+                        print!("Group number: ");
+                        io::stdout().flush().unwrap();
+                        let input = read_input()?;
+                        match input.trim().parse::<usize>().ok().and_then(|num| groups.get(num.wrapping_sub(1))) {
+                            Some((group, _)) if action.trim() == "1" => match tracker.mark_group_taken(group, &get_today()) {
+                                Ok(0) => println!("Everything in {} is already taken today.", group),
+                                Ok(count) => println!("Recorded {} dose{} from {}.", count, if count == 1 { "" } else { "s" }, group),
+                                Err(e) => println!("Error: {}", e),
+                            },
+                            Some((group, _)) => match tracker.remove_group(group) {
+                                Ok(_) => println!("Removed group {}.", group),
+                                Err(e) => println!("Error: {}", e),
+                            },
+                            None => println!("Invalid selection."),
+                        }
+                    }
+                    "2" => {
+                        print!("Group name (e.g. Morning packet): ");
+                        io::stdout().flush().unwrap();
+                        let name = read_input()?;
+                        
+                        print!("Medications in the group (comma-separated): ");
+                        io::stdout().flush().unwrap();
+                        let members = read_input()?;
+                        let members: Vec<String> = members.split(',')
+                            .map(|member| member.trim().to_string())
+                            .filter(|member| !member.is_empty())
+                            .collect();
+                        
+                        match tracker.add_group(&name, members) {
+                            Ok(_) => println!("Group {} created.", name.trim()),
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    }
+                    _ => println!("Invalid selection."),
+                }
+                wait_for_enter()?;
+            }
+            
+            "40" => {
+                clear_screen();
+                print_header(" REFILL ALL MEDICATIONS ");
+                
+                println!("1. Top every medication back up to its prescribed amount");
+                println!("2. Add the same amount to every medication");
+                print!("Select (1-2): ");
+                io::stdout().flush().unwrap();
+                let choice = read_input()?;
+                
+                let result = match choice.trim() {
+                    "1" => tracker.refill_all(true, None),
+                    "2" => {
+                        print!("Amount to add to each: ");
+                        io::stdout().flush().unwrap();
+                        let amount = read_input()?;
+                        tracker.refill_all(false, amount.trim().parse().ok())
+                    }
+                    _ => Err("Invalid selection.".to_string()),
+                };
+                match result {
+                    Ok(0) => println!("Nothing needed refilling."),
+                    Ok(count) => println!("Refilled {} medication{}!", count, if count == 1 { "" } else { "s" }),
+                    Err(e) => println!("Error: {}", e),
+                }
+                for warning in tracker.refill_warnings() {
+                    println!("REFILL: {}", warning);
+                }
+                wait_for_enter()?;
+            }
+            
+            "41" => {
+                clear_screen();
+                print_header(" WEEK START DAY ");
+                
+                println!("Weeks currently start on: {}", tracker.week_start_day());
+                print!("New start day (e.g. Mon or Sun, blank to keep): ");
+                io::stdout().flush().unwrap();
+                let input = read_input()?;
+                
+                if !input.trim().is_empty() {
+                    match input.trim().parse::<Weekday>() {
+                        Ok(day) => match tracker.set_week_start_day(day) {
+                            Ok(_) => println!("Weekly summaries will now start on {}.", day),
+                            Err(e) => println!("Error: {}", e),
+                        },
+                        Err(_) => println!("Error: Unknown weekday '{}'", input.trim()),
+                    }
+                }
+                wait_for_enter()?;
+            }
+            
+            "42" => {
+                clear_screen();
+                println!("{}", tr(locale, "Goodbye!"));
+                return Ok(());
+            }
+            
+            _ => {
+                println!("{}", tr(locale, "Invalid choice."));
+                wait_for_enter()?;
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use medication_tracker::{add_days, adherence_bar, get_today, Medication};
+
+fn day(offset: i64) -> String {
+    add_days(&get_today(), offset).unwrap()
+}
+
+#[test]
+fn full_and_partial_adherence_over_a_range() {
+    let mut tracker = common::tracker("full_and_partial_adherence_over_a_range");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    let mut tracker = common::backdate(tracker, &day(-10));
+    for offset in -4..=-1 {
+        tracker.mark_taken("Metformin", &day(offset), 1, true).unwrap();
+        tracker.mark_taken("Metformin", &day(offset), 2, offset >= -2).unwrap();
+    }
+    
+    assert_eq!(tracker.adherence_rate("Metformin", &day(-2), &day(-1)).unwrap(), 100.0);
+    assert_eq!(tracker.adherence_rate("Metformin", &day(-4), &day(-1)).unwrap(), 75.0);
+    assert_eq!(tracker.adherence_rate("Metformin", &day(-8), &day(-5)).unwrap(), 0.0);
+}
+
+#[test]
+fn empty_ranges_and_unknown_medications() {
+    let mut tracker = common::tracker("empty_ranges_and_unknown_medications");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    assert_eq!(tracker.adherence_rate("Lisinopril", &day(0), &day(-1)).unwrap(), 0.0);
+    assert_eq!(tracker.adherence_rate("Lisinopril", &day(-30), &day(-20)).unwrap(), 0.0);
+    assert!(tracker.adherence_rate("Aspirin", &day(-1), &day(0)).is_err());
+    assert!(tracker.adherence_rate("Lisinopril", "yesterday", &day(0)).is_err());
+}
+
+#[test]
+fn trend_over_three_improving_weeks() {
+    let mut tracker = common::tracker("trend_over_three_improving_weeks");
+    common::add(&mut tracker, "Lisinopril", "Morning", 90);
+    let this_week = tracker.week_start(&get_today());
+    let week = |weeks_back: i64| add_days(&this_week, -7 * weeks_back).unwrap();
+    let mut tracker = common::backdate(tracker, &week(5));
+    for (weeks_back, days_taken) in [(3, 2), (2, 4), (1, 7)] {
+        for offset in 0..days_taken {
+            tracker.mark_taken("Lisinopril", &add_days(&week(weeks_back), offset).unwrap(), 1, true).unwrap();
+        }
+    }
+    
+    let trend: Vec<(String, i32)> = tracker.adherence_trend(4, &week(1)).into_iter()
+        .map(|(start, percentage)| (start, percentage.round() as i32))
+        .collect();
+    assert_eq!(trend, vec![(week(4), 0), (week(3), 29), (week(2), 57), (week(1), 100)]);
+    assert!(!tracker.week_has_logs(&week(4)));
+    assert!(tracker.week_has_logs(&week(3)));
+}
+
+#[test]
+fn adherence_bar_is_ten_cells_wide() {
+    assert_eq!(adherence_bar(0.0), "[░░░░░░░░░░] 0%");
+    assert_eq!(adherence_bar(50.0), "[█████░░░░░] 50%");
+    assert_eq!(adherence_bar(100.0), "[██████████] 100%");
+    assert_eq!(adherence_bar(2.0), "[█░░░░░░░░░] 2%");
+    assert_eq!(adherence_bar(99.0), "[█████████░] 99%");
+    assert_eq!(adherence_bar(140.0), "[██████████] 100%");
+}
+
+#[test]
+fn only_the_bad_day_is_flagged() {
+    let mut tracker = common::tracker("only_the_bad_day_is_flagged");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Ibuprofen", "As needed", 30);
+    let mut tracker = common::backdate(tracker, &day(-10));
+    for offset in -3..=-1 {
+        tracker.mark_taken("Lisinopril", &day(offset), 1, true).unwrap();
+        if offset != -2 {
+            tracker.mark_taken("Metformin", &day(offset), 1, true).unwrap();
+        }
+    }
+    
+    let misses = tracker.days_with_misses(&day(-3), &day(-1));
+    assert_eq!(misses.len(), 1);
+    assert_eq!(misses[0].0, day(-2));
+    assert_eq!(misses[0].1.len(), 1);
+    assert!(misses[0].1[0].starts_with("Metformin"));
+}
+
+#[test]
+fn evening_bucket_lags_behind_morning() {
+    let mut tracker = common::tracker("evening_bucket_lags_behind_morning");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Aspirin", "07:30", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let mut tracker = common::backdate(tracker, &day(-4));
+    for offset in -4..=-1 {
+        tracker.mark_taken("Lisinopril", &day(offset), 1, true).unwrap();
+        tracker.mark_taken("Aspirin", &day(offset), 1, true).unwrap();
+        if offset == -1 {
+            tracker.mark_taken("Metformin", &day(offset), 1, true).unwrap();
+        }
+    }
+    
+    let by_bucket = tracker.adherence_by_time_of_day(&day(-4), &day(-1));
+    assert_eq!(by_bucket.len(), 2);
+    assert_eq!(by_bucket["Morning"], 100.0);
+    assert_eq!(by_bucket["Evening"], 25.0);
+    assert!(!by_bucket.contains_key("Bedtime"));
+}
+
+#[test]
+fn days_without_entries_are_not_recorded_rather_than_missed() {
+    let mut tracker = common::tracker("days_without_entries_are_not_recorded_rather_than_missed");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let mut tracker = common::backdate(tracker, "2024-01-01");
+    for date in ["2024-01-01", "2024-01-02", "2024-01-05", "2024-01-06", "2024-01-07"] {
+        tracker.mark_taken("Lisinopril", date, 1, true).unwrap();
+    }
+    tracker.mark_taken("Lisinopril", "2024-01-03", 1, false).unwrap();
+    
+    let summary = tracker.generate_weekly_summary("2024-01-01").unwrap();
+    assert!(summary.contains("Wed: 0/1 medications taken - MISSED: Lisinopril"));
+    assert!(summary.contains("Thu: nothing recorded yet\n"));
+    assert!(summary.contains("NOT YET RECORDED: Thu\n"));
+    assert_eq!(tracker.unlogged_dates("2024-01-01", "2024-01-07"), ["2024-01-04"]);
+}
+
+#[test]
+fn ranking_goes_from_best_to_worst() {
+    let mut tracker = common::tracker("ranking_goes_from_best_to_worst");
+    for name in ["Metformin", "Vitamin D", "Lisinopril", "Aspirin", "Zinc"] {
+        common::add(&mut tracker, name, "Morning", 30);
+    }
+    common::add(&mut tracker, "Ibuprofen", "As needed", 30);
+    let mut tracker = common::backdate(tracker, &day(-4));
+    for offset in -4..=-1 {
+        tracker.mark_taken("Vitamin D", &day(offset), 1, true).unwrap();
+        if offset < -1 {
+            tracker.mark_taken("Lisinopril", &day(offset), 1, true).unwrap();
+            tracker.mark_taken("Aspirin", &day(offset), 1, true).unwrap();
+        }
+        if offset == -4 {
+            tracker.mark_taken("Metformin", &day(offset), 1, true).unwrap();
+        }
+    }
+    tracker.set_paused("Zinc", true).unwrap();
+    
+    assert_eq!(tracker.adherence_ranking(&day(-4), &day(-1)), [
+        ("Vitamin D".to_string(), 100.0),
+        ("Aspirin".to_string(), 75.0),
+        ("Lisinopril".to_string(), 75.0),
+        ("Metformin".to_string(), 25.0),
+    ]);
+}
+
+#[test]
+fn unlogged_dates_are_the_gap_days() {
+    let mut tracker = common::tracker("unlogged_dates_are_the_gap_days");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Ibuprofen", "As needed", 30);
+    let mut tracker = common::backdate(tracker, &day(-5));
+    for offset in [-5, -4, -1] {
+        tracker.mark_taken("Lisinopril", &day(offset), 1, offset != -4).unwrap();
+    }
+    
+    assert_eq!(tracker.unlogged_dates(&day(-5), &day(-1)), [day(-3), day(-2)]);
+    assert!(tracker.unlogged_dates(&day(-9), &day(-4)).is_empty());
+}
+
+#[test]
+fn pausing_keeps_the_days_before_the_pause() {
+    let mut tracker = common::tracker("pausing_keeps_the_days_before_the_pause");
+    common::add(&mut tracker, "Zinc", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-3));
+    for offset in -3..=-1 {
+        tracker.mark_taken("Zinc", &day(offset), 1, true).unwrap();
+    }
+    tracker.set_paused("Zinc", true).unwrap();
+    let tracker = common::reopen(tracker);
+    
+    assert_eq!(tracker.adherence_rate("Zinc", &day(-3), &day(-1)), Ok(100.0));
+    assert!(tracker.generate_clinical_report(&day(-3), &day(-1)).contains("Zinc 10mg: 100.0% (paused)"));
+    let med = tracker.get_medication("Zinc").unwrap();
+    assert!(med.is_due_on(&day(-1)) && !med.paused_on(&day(-1)));
+    assert!(!med.is_due_on(&get_today()));
+}
+
+#[test]
+fn pause_periods_round_trip_and_end_on_resume() {
+    let med = Medication::try_from("Zinc,10mg,Morning,30,30,1,7,,2024-01-01,,Normal,0,Daily,,60,0,1,0,0,,,2024-01-05:2024-01-08;2024-01-20:").unwrap();
+    
+    assert!(med.is_due_on("2024-01-04") && med.is_due_on("2024-01-08") && med.is_due_on("2024-01-19"));
+    assert!(!med.is_due_on("2024-01-05") && !med.is_due_on("2024-01-07") && !med.is_due_on("2024-03-01"));
+    assert_eq!(Medication::try_from(med.to_csv_line().as_str()).unwrap().pauses, med.pauses);
+    
+    let mut tracker = common::tracker("pause_periods_round_trip_and_end_on_resume");
+    common::add(&mut tracker, "Zinc", "Morning", 30);
+    tracker.set_paused("Zinc", true).unwrap();
+    tracker.set_paused("Zinc", false).unwrap();
+    assert!(tracker.get_medication("Zinc").unwrap().pauses.is_empty());
+    assert!(tracker.get_medication("Zinc").unwrap().is_due_on(&get_today()));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use medication_tracker::{get_today, slugify};
+use std::fs;
+
+#[test]
+fn actions_are_appended_in_order() {
+    let mut tracker = common::tracker("actions_are_appended_in_order");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    
+    let events: Vec<String> = tracker.read_audit().unwrap()
+        .iter()
+        .map(|line| line.split_once(',').unwrap().1.to_string())
+        .collect();
+    assert_eq!(events, ["add_medication,Lisinopril", "mark_taken,Lisinopril,true"]);
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.read_audit().unwrap().len(), 2);
+}
+
+#[test]
+fn audit_lines_wait_for_the_save() {
+    let mut tracker = common::tracker("audit_lines_wait_for_the_save");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let audit_file = tracker.data_dir().join(format!("{}_audit.txt", slugify(tracker.patient_name())));
+    
+    assert!(!audit_file.exists());
+    assert_eq!(tracker.read_audit().unwrap().len(), 1);
+    tracker.save_all().unwrap();
+    assert_eq!(fs::read_to_string(&audit_file).unwrap().lines().count(), 1);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn closing_input_mid_flow_releases_the_lock() {
+    let dir = common::data_dir("closing_input_mid_flow_releases_the_lock");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+        .args(["--patient", "Test Patient"])
+        .env("MEDITRACK_DATA_DIR", &dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Start adding a medication, then close input at the name prompt.
+    child.stdin.take().unwrap().write_all(b"5\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Input closed"));
+    assert!(!dir.join("test_patient.lock").exists());
+    medication_tracker::MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+}
+
+#[test]
+fn adding_an_as_needed_medication_skips_the_prn_question() {
+    let dir = common::data_dir("adding_an_as_needed_medication_skips_the_prn_question");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+        .args(["--patient", "Test Patient"])
+        .env("MEDITRACK_DATA_DIR", &dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Name, dosage, "As needed", doses per day, quantity, notes, maximum supply, then priority.
+    child.stdin.take().unwrap().write_all(b"5\nIbuprofen\n200mg\n5\n\n20\n\n\nHigh\n").unwrap();
+    assert!(child.wait_with_output().unwrap().status.success());
+    
+    let tracker = medication_tracker::MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    let med = tracker.get_medication("Ibuprofen").unwrap();
+    assert!(med.is_prn);
+    assert_eq!(med.priority, medication_tracker::Priority::High);
+}
+
+#[test]
+fn mark_taken_command_records_the_next_dose() {
+    let mut tracker = common::tracker("mark_taken_command_records_the_next_dose");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    for _ in 0..3 {
+        Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+            .args(["--patient", "Test Patient", "--mark-taken", "Metformin"])
+            .env("MEDITRACK_DATA_DIR", &dir)
+            .output()
+            .unwrap();
+    }
+    let tracker = medication_tracker::MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.doses_taken("Metformin", &get_today()), 2);
+    assert!(tracker.next_untaken_dose("Metformin", &get_today()).is_err());
+}
+
+#[test]
+fn missed_today_command_lists_missed_medications() {
+    let mut tracker = common::tracker("missed_today_command_lists_missed_medications");
+    common::add(&mut tracker, "Lisinopril", "00:00", 30);
+    common::add(&mut tracker, "Metformin", "00:00", 30);
+    tracker.mark_taken("Metformin", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+        .args(["--patient", "Test Patient", "--missed-today"])
+        .env("MEDITRACK_DATA_DIR", &dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // Whether Lisinopril is listed depends on the clock, but a taken medication never is.
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Metformin"));
+}
+
+#[test]
+fn missed_medications_for_a_past_day() {
+    let mut tracker = common::tracker("missed_medications_for_a_past_day");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 30);
+    let yesterday = add_days(&get_today(), -1).unwrap();
+    let mut tracker = common::backdate(tracker, &yesterday);
+    tracker.mark_taken("Metformin", &yesterday, 1, true).unwrap();
+    
+    let missed = tracker.get_missed_medications(&yesterday).unwrap();
+    assert_eq!(missed.len(), 1);
+    assert!(missed[0].contains("Lisinopril"));
+}
+
+#[test]
+fn commands_need_a_patient() {
+    let output = Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+        .arg("--missed-today")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn end_of_input_exits_cleanly() {
+    let dir = common::data_dir("end_of_input_exits_cleanly");
+    for args in [&[][..], &["--patient", "Test Patient"][..]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_medication_tracker"))
+            .args(args)
+            .env("MEDITRACK_DATA_DIR", &dir)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        
+        assert!(output.status.success());
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+    }
+    assert!(!dir.join("test_patient.lock").exists());
+}
@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+use medication_tracker::{slugify, Medication, MedicationTracker};
+use std::fs;
+use std::path::PathBuf;
+
+/// An empty data directory private to one test.
+pub fn data_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("medication_tracker_{}_{}", std::process::id(), test_name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+pub fn tracker(test_name: &str) -> MedicationTracker {
+    MedicationTracker::with_data_dir("Test Patient", &data_dir(test_name)).unwrap()
+}
+
+pub fn add(tracker: &mut MedicationTracker, name: &str, time_of_day: &str, count: u32) {
+    tracker.add_medication(name.to_string(), "10mg".to_string(), time_of_day.to_string(), count, 1, String::new()).unwrap();
+}
+
+/// Saves, then reopens the tracker with every medication's `added_on` moved back to `date` so
+/// doses can be logged on earlier days, the same as a meds file written back then would.
+pub fn backdate(tracker: MedicationTracker, date: &str) -> MedicationTracker {
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    let patient = tracker.patient_name().to_string();
+    drop(tracker);
+    
+    let meds_file = dir.join(format!("{}_meds.txt", slugify(&patient)));
+    let mut contents = String::new();
+    for line in fs::read_to_string(&meds_file).unwrap().lines() {
+        let mut med = Medication::try_from(line).unwrap();
+        med.added_on = date.to_string();
+        contents.push_str(&med.to_csv_line());
+        contents.push('\n');
+    }
+    fs::write(&meds_file, contents).unwrap();
+    MedicationTracker::with_data_dir(&patient, &dir).unwrap()
+}
+
+pub fn reopen(tracker: MedicationTracker) -> MedicationTracker {
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    let patient = tracker.patient_name().to_string();
+    drop(tracker);
+    MedicationTracker::with_data_dir(&patient, &dir).unwrap()
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use medication_tracker::{get_today, Quantity};
+
+#[test]
+fn estimated_monthly_cost_sums_priced_medications() {
+    let mut tracker = common::tracker("estimated_monthly_cost_sums_priced_medications");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    common::add(&mut tracker, "Vitamin D", "Morning", 90);
+    tracker.set_cost_per_unit("Lisinopril", Some(0.5)).unwrap();
+    tracker.set_cost_per_unit("Metformin", Some(0.25)).unwrap();
+    
+    assert!((tracker.estimated_monthly_cost() - 30.0).abs() < 0.01);
+}
+
+#[test]
+fn medication_list_shows_cost_per_dose() {
+    let mut tracker = common::tracker("medication_list_shows_cost_per_dose");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Vitamin D", "Morning", 90);
+    tracker.set_cost_per_unit("Lisinopril", Some(0.5)).unwrap();
+    
+    let list = tracker.list_medications(&get_today());
+    assert!(list.iter().any(|line| line.starts_with("Lisinopril") && line.ends_with(" - $0.50/dose (~$15.00/month)")));
+    assert!(list.iter().any(|line| line.starts_with("Vitamin D") && line.ends_with(" - no cost set")));
+}
+
+#[test]
+fn doses_of_several_pills_cost_every_pill() {
+    let mut tracker = common::tracker("doses_of_several_pills_cost_every_pill");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.set_units_per_dose("Lisinopril", Quantity::whole(2)).unwrap();
+    tracker.set_cost_per_unit("Lisinopril", Some(0.5)).unwrap();
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    
+    assert!((tracker.estimated_monthly_cost() - 30.0).abs() < 0.01);
+    let (year, month) = (today[..4].parse().unwrap(), today[5..7].parse().unwrap());
+    assert!((tracker.actual_monthly_cost(year, month) - 1.0).abs() < 0.01);
+    let list = tracker.list_medications(&today);
+    assert!(list.iter().any(|line| line.ends_with(" - $1.00/dose (~$30.00/month)")));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+
+fn day(offset: i64) -> String {
+    add_days(&get_today(), offset).unwrap()
+}
+
+#[test]
+fn future_course_gives_no_streak_and_does_not_hang() {
+    let mut tracker = common::tracker("future_course_gives_no_streak_and_does_not_hang");
+    common::add(&mut tracker, "Amoxicillin", "Morning", 30);
+    tracker.mark_taken("Amoxicillin", &get_today(), 1, true).unwrap();
+    tracker.set_course_dates("Amoxicillin", &day(5), &day(14)).unwrap();
+    let tracker = common::backdate(tracker, "");
+    
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 0);
+}
+
+#[test]
+fn not_missed_outside_course() {
+    let mut tracker = common::tracker("not_missed_outside_course");
+    common::add(&mut tracker, "Amoxicillin", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-20));
+    tracker.set_course_dates("Amoxicillin", &day(-10), &day(-5)).unwrap();
+    
+    assert!(tracker.get_missed_medications(&day(-11)).unwrap().is_empty());
+    assert_eq!(tracker.get_missed_medications(&day(-7)).unwrap().len(), 1);
+    assert!(tracker.get_missed_medications(&day(-4)).unwrap().is_empty());
+    assert_eq!(tracker.days_with_misses(&day(-20), &day(-1)).len(), 6);
+}
+
+#[test]
+fn calendar_export_stops_at_the_course_end() {
+    let mut tracker = common::tracker("calendar_export_stops_at_the_course_end");
+    common::add(&mut tracker, "Amoxicillin", "Morning", 30);
+    common::add(&mut tracker, "Lisinopril", "Evening", 30);
+    tracker.set_course_dates("Amoxicillin", &day(2), &day(5)).unwrap();
+    
+    let ics = std::fs::read_to_string(tracker.export_ics(&get_today(), 30).unwrap()).unwrap();
+    let start = day(2).replace('-', "");
+    let until = |offset| day(offset).replace('-', "") + "T235959";
+    assert!(ics.contains(&format!("DTSTART:{}T080000\r\nDURATION:PT15M\r\nRRULE:FREQ=DAILY;UNTIL={}\r\n", start, until(5))));
+    assert!(ics.contains(&format!("RRULE:FREQ=DAILY;UNTIL={}\r\n", until(29))));
+}
+
+#[test]
+fn finished_courses_wait_out_the_grace_days() {
+    let mut tracker = common::tracker("finished_courses_wait_out_the_grace_days");
+    common::add(&mut tracker, "Amoxicillin", "Morning", 30);
+    common::add(&mut tracker, "Prednisone", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-20));
+    tracker.set_course_dates("Amoxicillin", &day(-14), &day(-4)).unwrap();
+    tracker.set_course_dates("Prednisone", &day(-14), &day(-3)).unwrap();
+    
+    let finished = vec![("Amoxicillin".to_string(), day(-4))];
+    assert_eq!(tracker.finished_courses(&get_today()), finished);
+    assert_eq!(tracker.finished_courses(&get_today().replace('-', "/")), finished);
+    assert!(tracker.finished_courses("not a date").is_empty());
+}
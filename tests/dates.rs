@@ -0,0 +1,103 @@
+mod common;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use medication_tracker::{add_days, date_range, get_today, get_week_start, normalize_date};
+
+#[test]
+fn add_days_crosses_leap_day() {
+    assert_eq!(add_days("2024-02-28", 1).as_deref(), Some("2024-02-29"));
+    assert_eq!(add_days("2024-02-29", 1).as_deref(), Some("2024-03-01"));
+    assert_eq!(add_days("2023-02-28", 1).as_deref(), Some("2023-03-01"));
+}
+
+#[test]
+fn add_days_crosses_month_and_year_ends() {
+    assert_eq!(add_days("2026-04-30", 1).as_deref(), Some("2026-05-01"));
+    assert_eq!(add_days("2023-12-31", 1).as_deref(), Some("2024-01-01"));
+}
+
+#[test]
+fn add_days_goes_backwards() {
+    assert_eq!(add_days("2024-03-01", -1).as_deref(), Some("2024-02-29"));
+    assert_eq!(add_days("2023-03-01", -1).as_deref(), Some("2023-02-28"));
+    assert_eq!(add_days("2024-01-01", -1).as_deref(), Some("2023-12-31"));
+    assert_eq!(add_days("2024-01-10", -40).as_deref(), Some("2023-12-01"));
+}
+
+#[test]
+fn add_days_rejects_bad_input() {
+    assert_eq!(add_days("not a date", 1), None);
+    assert_eq!(add_days("2024-02-30", 1), None);
+    assert_eq!(add_days("2024-01-01", i64::MAX), None);
+}
+
+#[test]
+fn date_range_spans_leap_day_and_new_year() {
+    assert_eq!(date_range("2024-02-27", "2024-03-01"), ["2024-02-27", "2024-02-28", "2024-02-29", "2024-03-01"]);
+    assert_eq!(date_range("2023-12-30", "2024-01-02"), ["2023-12-30", "2023-12-31", "2024-01-01", "2024-01-02"]);
+    assert!(date_range("2024-01-02", "2024-01-01").is_empty());
+}
+
+#[test]
+fn today_and_week_start_are_real_dates() {
+    let today = NaiveDate::parse_from_str(&get_today(), "%Y-%m-%d").unwrap();
+    let monday = NaiveDate::parse_from_str(&get_week_start(), "%Y-%m-%d").unwrap();
+    
+    assert_eq!(monday.weekday(), Weekday::Mon);
+    assert!(monday <= today && (today - monday).num_days() < 7);
+}
+
+#[test]
+fn weekly_summary_counts_doses_on_real_dates() {
+    let mut tracker = common::tracker("weekly_summary_counts_doses_on_real_dates");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let last_week = add_days(&get_week_start(), -7).unwrap();
+    let mut tracker = common::backdate(tracker, &add_days(&last_week, -7).unwrap());
+    tracker.mark_taken("Lisinopril", &add_days(&last_week, 3).unwrap(), 1, true).unwrap();
+    
+    let summary = tracker.generate_weekly_summary(&last_week).unwrap();
+    assert!(summary.contains(&format!("Week starting: {}", last_week)));
+    assert!(summary.contains("Adherence: 1/7 days"));
+}
+
+#[test]
+fn common_date_formats_normalize() {
+    for input in ["2024-03-05", " 2024/03/05 ", "2024.03.05", "5 Mar 2024", "05 March 2024", "Mar 5, 2024", "March 5, 2024"] {
+        assert_eq!(normalize_date(input), Ok("2024-03-05".to_string()), "{}", input);
+    }
+    for input in ["not-a-date", "2024-02-30", "03/05/2024", ""] {
+        assert!(normalize_date(input).is_err(), "{} should be rejected", input);
+    }
+}
+
+#[test]
+fn doses_marked_with_other_formats_land_on_the_same_day() {
+    let mut tracker = common::tracker("doses_marked_with_other_formats_land_on_the_same_day");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Morning".to_string(), 60, 2, String::new()).unwrap();
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    tracker.mark_taken("Metformin", "2024/03/05", 1, true).unwrap();
+    tracker.mark_taken("Metformin", "5 Mar 2024", 2, true).unwrap();
+    
+    assert_eq!(tracker.doses_taken("Metformin", "2024-03-05"), 2);
+    assert!(tracker.mark_taken("Metformin", "not-a-date", 1, true).unwrap_err().contains("Invalid date"));
+}
+
+#[test]
+fn status_and_history_queries_normalize_or_reject_dates() {
+    let mut tracker = common::tracker("status_and_history_queries_normalize_or_reject_dates");
+    common::add(&mut tracker, "Metformin", "Morning", 30);
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    tracker.mark_taken("Metformin", "2024-03-04", 1, true).unwrap();
+    
+    assert_eq!(tracker.get_missed_medications("2024/03/05").unwrap(), vec!["Metformin at Morning".to_string()]);
+    assert_eq!(tracker.current_streak("4 Mar 2024").unwrap(), 1);
+    assert!(tracker.daily_digest("Mar 4, 2024").unwrap().contains("2024-03-04"));
+    for input in ["not-a-date", "2024-02-30"] {
+        assert!(tracker.get_missed_medications(input).is_err(), "{}", input);
+        assert!(tracker.current_streak(input).is_err(), "{}", input);
+        assert!(tracker.today_status(input).is_err(), "{}", input);
+        assert!(tracker.check_today_status(input).is_err(), "{}", input);
+        assert!(tracker.daily_digest(input).is_err(), "{}", input);
+        assert!(tracker.generate_weekly_summary(input).is_err(), "{}", input);
+    }
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, slugify, MedicationTracker, Quantity};
+use std::fs;
+
+#[test]
+fn third_dose_is_still_flagged() {
+    let mut tracker = common::tracker("third_dose_is_still_flagged");
+    tracker.add_medication("Amoxicillin".to_string(), "500mg".to_string(), "Morning".to_string(), 21, 3, String::new()).unwrap();
+    let today = get_today();
+    tracker.mark_taken("Amoxicillin", &today, 1, true).unwrap();
+    tracker.mark_taken("Amoxicillin", &today, 2, true).unwrap();
+    
+    let status = tracker.today_status(&today).unwrap();
+    assert_eq!((status[0].doses_taken, status[0].doses_per_day), (2, 3));
+    assert!(status[0].reminder_needed);
+    let (_, details, taken, _) = &tracker.check_today_status(&today).unwrap()[0];
+    assert!(details.ends_with("2/3 doses taken"));
+    assert!(!taken);
+    
+    tracker.mark_taken("Amoxicillin", &today, 3, true).unwrap();
+    assert!(!tracker.today_status(&today).unwrap()[0].reminder_needed);
+    assert!(tracker.mark_taken("Amoxicillin", &today, 4, true).is_err());
+}
+
+#[test]
+fn log_lines_without_a_dose_count_as_dose_one() {
+    let mut tracker = common::tracker("log_lines_without_a_dose_count_as_dose_one");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let today = get_today();
+    fs::write(dir.join(format!("{}_logs.txt", slugify("Test Patient"))), format!("{},Lisinopril,1\n", today)).unwrap();
+    let tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    assert!(tracker.load_warnings().is_empty());
+}
+
+#[test]
+fn taken_time_is_recorded_and_reloaded() {
+    let mut tracker = common::tracker("taken_time_is_recorded_and_reloaded");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    
+    let time = tracker.today_status(&today).unwrap()[0].last_taken_time.clone().unwrap();
+    assert_eq!(time.len(), 8);
+    assert!(tracker.check_today_status(&today).unwrap()[0].3.starts_with(&format!("Taken at {}", &time[..5])));
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.today_status(&today).unwrap()[0].last_taken_time, Some(time));
+}
+
+#[test]
+fn mark_all_taken_skips_doses_already_taken() {
+    let mut tracker = common::tracker("mark_all_taken_skips_doses_already_taken");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 0);
+    common::add(&mut tracker, "Ibuprofen", "As needed", 20);
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    
+    assert_eq!(tracker.mark_all_taken(&today).unwrap(), 2);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(58));
+    assert_eq!(tracker.doses_taken("Atorvastatin", &today), 1);
+    assert_eq!(tracker.get_medication("Atorvastatin").unwrap().current_count, Quantity::ZERO);
+    assert_eq!(tracker.doses_taken("Ibuprofen", &today), 0);
+    assert_eq!(tracker.mark_all_taken(&today).unwrap(), 0);
+}
+
+#[test]
+fn skipped_dose_is_not_missed_but_shows_in_the_summary() {
+    let mut tracker = common::tracker("skipped_dose_is_not_missed_but_shows_in_the_summary");
+    common::add(&mut tracker, "Warfarin", "Morning", 30);
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let mut tracker = common::backdate(tracker, "2024-01-01");
+    tracker.skip_dose("Warfarin", "2024-01-03", 1, "doctor said hold").unwrap();
+    
+    assert_eq!(tracker.get_missed_medications("2024-01-03").unwrap(), vec!["Lisinopril at Morning".to_string()]);
+    let summary = tracker.generate_weekly_summary("2024-01-01").unwrap();
+    assert!(summary.contains("Wed [S]"));
+    assert!(summary.contains("Skipped: Wed (doctor said hold)"));
+    assert!(summary.contains("Adherence: 0/7 days"));
+}
+
+#[test]
+fn relogged_dose_moves_to_the_next_day() {
+    let mut tracker = common::tracker("relogged_dose_moves_to_the_next_day");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    tracker.mark_taken("Lisinopril", "2024-03-02", 1, true).unwrap();
+    
+    tracker.relog_dose("Lisinopril", "2024-03-02", "2024-03-03").unwrap();
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-02"), 0);
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-03"), 1);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    
+    assert!(tracker.relog_dose("Lisinopril", "2024-03-02", "2024-03-04").is_err());
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-03"), 1);
+}
+
+#[test]
+fn conflicting_log_lines_prefer_taken_and_collapse_on_save() {
+    let mut tracker = common::tracker("conflicting_log_lines_prefer_taken_and_collapse_on_save");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let log_file = dir.join(format!("{}_logs.txt", slugify("Test Patient")));
+    fs::write(&log_file, "2024-03-02,Lisinopril,0,1\n2024-03-02,Lisinopril,1,1\n2024-03-02,Lisinopril,0,1\n").unwrap();
+    let mut tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-02"), 1);
+    
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    let saved = fs::read_to_string(&log_file).unwrap();
+    assert_eq!(saved.lines().filter(|line| line.starts_with("2024-03-02,")).count(), 1);
+}
+
+#[test]
+fn doses_can_be_logged_for_past_days_but_not_future_ones() {
+    let mut tracker = common::tracker("doses_can_be_logged_for_past_days_but_not_future_ones");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    let yesterday = add_days(&today, -1).unwrap();
+    let mut tracker = common::backdate(tracker, &yesterday);
+    
+    tracker.mark_taken("Lisinopril", &yesterday, 1, true).unwrap();
+    assert_eq!(tracker.doses_taken("Lisinopril", &yesterday), 1);
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 0);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert!(tracker.generate_weekly_summary(&yesterday).unwrap().contains("Adherence: 1/"));
+    
+    let tomorrow = add_days(&today, 1).unwrap();
+    assert!(tracker.mark_taken("Lisinopril", &tomorrow, 1, true).is_err());
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+}
+
+#[test]
+fn caregiver_attribution_survives_reloading() {
+    let mut tracker = common::tracker("caregiver_attribution_survives_reloading");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    let mut tracker = common::backdate(tracker, "2024-01-01");
+    tracker.mark_taken_by("Metformin", "2024-01-03", 1, "Nurse Kim").unwrap();
+    tracker.mark_taken_by("Metformin", "2024-01-03", 2, "self").unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.doses_taken("Metformin", "2024-01-03"), 2);
+    let summary = tracker.generate_weekly_summary("2024-01-01").unwrap();
+    assert!(summary.contains("Given by caregiver: Wed (Nurse Kim)\n"));
+    let audit = tracker.read_audit().unwrap();
+    assert!(audit.iter().any(|line| line.ends_with("mark_taken,Metformin,true,Nurse Kim")));
+    assert!(audit.iter().any(|line| line.ends_with("mark_taken,Metformin,true,self")));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use medication_tracker::{get_today, slugify, Medication, MedicationTracker, Quantity};
+use std::fs;
+
+#[test]
+fn a_file_in_place_of_the_data_directory_is_an_error() {
+    let path = common::data_dir("a_file_in_place_of_the_data_directory_is_an_error");
+    fs::write(&path, "not a directory").unwrap();
+    
+    assert!(MedicationTracker::with_data_dir("Test Patient", &path).is_err());
+}
+
+#[test]
+fn failed_writes_are_reported_not_panicked() {
+    let mut tracker = common::tracker("failed_writes_are_reported_not_panicked");
+    tracker.set_immediate_persistence(true).unwrap();
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    // A directory where the temporary files go makes every write fail.
+    let stem = slugify(tracker.patient_name());
+    fs::create_dir(tracker.data_dir().join(format!("{}_meds.tmp", stem))).unwrap();
+    fs::create_dir(tracker.data_dir().join(format!("{}_logs.tmp", stem))).unwrap();
+    
+    assert!(tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 1, String::new()).is_err());
+    assert!(tracker.mark_taken("Lisinopril", &get_today(), 1, true).is_err());
+    assert!(tracker.refill_medication("Lisinopril", Quantity::whole(10)).is_err());
+}
+
+#[test]
+fn a_failed_save_leaves_the_old_file_whole() {
+    let mut tracker = common::tracker("a_failed_save_leaves_the_old_file_whole");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let meds_file = tracker.data_dir().join(format!("{}_meds.txt", slugify(tracker.patient_name())));
+    let saved = fs::read_to_string(&meds_file).unwrap();
+    
+    fs::create_dir(meds_file.with_extension("tmp")).unwrap();
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    assert!(tracker.save_all().is_err());
+    assert_eq!(fs::read_to_string(&meds_file).unwrap(), saved);
+    
+    fs::remove_dir(meds_file.with_extension("tmp")).unwrap();
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names().len(), 2);
+}
+
+#[test]
+fn a_half_written_temp_file_is_ignored() {
+    let mut tracker = common::tracker("a_half_written_temp_file_is_ignored");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let meds_file = tracker.data_dir().join(format!("{}_meds.txt", slugify(tracker.patient_name())));
+    fs::write(meds_file.with_extension("tmp"), "Metformin,500").unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names(), vec!["Lisinopril".to_string()]);
+    assert!(tracker.load_warnings().is_empty());
+}
+
+#[test]
+fn malformed_lines_are_reported_and_kept() {
+    let mut tracker = common::tracker("malformed_lines_are_reported_and_kept");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let meds_file = dir.join(format!("{}_meds.txt", slugify("Test Patient")));
+    let good = fs::read_to_string(&meds_file).unwrap();
+    fs::write(&meds_file, format!("{}Metformin,500mg\n", good)).unwrap();
+    
+    let mut tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.medication_names(), ["Lisinopril"]);
+    assert_eq!(tracker.load_warnings().len(), 1);
+    assert!(tracker.load_warnings()[0].contains("Metformin,500mg"));
+    assert_eq!(fs::read_to_string(meds_file.with_extension("corrupt")).unwrap(), "Metformin,500mg\n");
+    
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    drop(tracker);
+    let tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert!(tracker.load_warnings().is_empty());
+    assert_eq!(fs::read_to_string(meds_file.with_extension("corrupt")).unwrap(), "Metformin,500mg\n");
+}
+
+#[test]
+fn repair_fixes_what_verify_reports() {
+    let mut tracker = common::tracker("repair_fixes_what_verify_reports");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let stem = slugify("Test Patient");
+    let meds_file = dir.join(format!("{}_meds.txt", stem));
+    let mut med = Medication::try_from(fs::read_to_string(&meds_file).unwrap().trim()).unwrap();
+    med.current_count = Quantity::whole(40);
+    fs::write(&meds_file, format!("{}\n", med.to_csv_line())).unwrap();
+    let log_file = dir.join(format!("{}_logs.txt", stem));
+    let logs = fs::read_to_string(&log_file).unwrap();
+    fs::write(&log_file, format!("{}2024-03-02,Metformin,1,1\n", logs)).unwrap();
+    
+    let mut tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    let problems = tracker.verify_integrity();
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].contains("Lisinopril has 40 doses left"));
+    assert!(problems[1].contains("Metformin"));
+    
+    assert_eq!(tracker.repair().unwrap(), 2);
+    assert!(tracker.verify_integrity().is_empty());
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.doses_taken("Lisinopril", &get_today()), 1);
+    
+    let tracker = common::reopen(tracker);
+    assert!(tracker.verify_integrity().is_empty());
+}
+
+#[test]
+fn failed_repair_saves_are_reported() {
+    let mut tracker = common::tracker("failed_repair_saves_are_reported");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    let stem = slugify("Test Patient");
+    let log_file = dir.join(format!("{}_logs.txt", stem));
+    fs::write(&log_file, "2024-03-02,Metformin,1,1\n").unwrap();
+    let mut tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    tracker.set_immediate_persistence(true).unwrap();
+    fs::create_dir(dir.join(format!("{}_logs.tmp", stem))).unwrap();
+    
+    assert!(tracker.repair().is_err());
+    fs::remove_dir(dir.join(format!("{}_logs.tmp", stem))).unwrap();
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+
+#[test]
+fn jsonl_export_writes_one_dose_per_line_in_date_order() {
+    let mut tracker = common::tracker("jsonl_export_writes_one_dose_per_line_in_date_order");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    let (two_days_ago, yesterday) = (add_days(&today, -2).unwrap(), add_days(&today, -1).unwrap());
+    let mut tracker = common::backdate(tracker, &two_days_ago);
+    tracker.mark_taken("Lisinopril", &yesterday, 1, true).unwrap();
+    tracker.mark_taken("Lisinopril", &two_days_ago, 1, false).unwrap();
+    
+    let mut out = Vec::new();
+    tracker.export_log_jsonl(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format!(
+        "{{\"date\":\"{}\",\"medication\":\"Lisinopril\",\"dose\":1,\"status\":\"missed\"}}\n\
+         {{\"date\":\"{}\",\"medication\":\"Lisinopril\",\"dose\":1,\"status\":\"taken\"}}\n",
+        two_days_ago, yesterday));
+}
+
+#[test]
+fn calendar_export_is_well_formed() {
+    let mut tracker = common::tracker("calendar_export_is_well_formed");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Atorvastatin", "21:30", 30);
+    common::add(&mut tracker, "Ibuprofen", "As needed", 30);
+    
+    let ics = std::fs::read_to_string(tracker.export_ics(&get_today(), 7).unwrap()).unwrap();
+    let lines: Vec<&str> = ics.split("\r\n").filter(|line| !line.is_empty()).collect();
+    assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+    assert_eq!(lines.last(), Some(&"END:VCALENDAR"));
+    assert!(lines.contains(&"VERSION:2.0"));
+    
+    let mut depth = 0;
+    let mut events = 0;
+    for line in &lines[1..lines.len() - 1] {
+        match *line {
+            "BEGIN:VEVENT" => {
+                assert_eq!(depth, 0);
+                depth += 1;
+                events += 1;
+            }
+            "END:VEVENT" => {
+                assert_eq!(depth, 1);
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(depth, 0);
+    assert_eq!(events, 2);
+    assert!(ics.contains("SUMMARY:Take Lisinopril (10mg)\r\n"));
+    assert!(ics.contains("T080000\r\n"));
+    assert!(ics.contains("T213000\r\n"));
+    assert!(!ics.contains("Ibuprofen"));
+}
+
+#[test]
+fn long_calendar_lines_are_folded() {
+    let mut tracker = common::tracker("long_calendar_lines_are_folded");
+    let notes = "Take with a full glass of water, at least thirty minutes before breakfast – never with grapefruit juice";
+    tracker.add_medication("Lisinopril".to_string(), "10mg".to_string(), "Morning".to_string(), 30, 1, notes.to_string()).unwrap();
+    
+    let ics = std::fs::read_to_string(tracker.export_ics(&get_today(), 7).unwrap()).unwrap();
+    assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    assert!(ics.contains("\r\n "));
+    assert!(ics.replace("\r\n ", "").contains("DESCRIPTION:Take with a full glass of water\\, at least thirty minutes before breakfast – never with grapefruit juice\r\n"));
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use medication_tracker::{get_today, Quantity};
+
+#[test]
+fn marking_a_group_takes_every_member() {
+    let mut tracker = common::tracker("marking_a_group_takes_every_member");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Aspirin", "Morning", 20);
+    common::add(&mut tracker, "Vitamin D", "Morning", 30);
+    tracker.add_group("Morning packet", vec!["Lisinopril".to_string(), "Aspirin".to_string()]).unwrap();
+    assert!(tracker.add_group("Bad packet", vec!["Lisinopril".to_string(), "Warfarin".to_string()]).is_err());
+    let today = get_today();
+    
+    let reminders = tracker.group_reminder_entries(tracker.active_reminder_entries(&today, "08:30"));
+    assert_eq!(reminders.len(), 2);
+    assert!(reminders.iter().any(|(name, message)| name == "Morning packet" && message.starts_with("Morning packet: ")));
+    
+    assert_eq!(tracker.mark_group_taken("Morning packet", &today), Ok(2));
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.get_medication("Aspirin").unwrap().current_count, Quantity::whole(19));
+    assert_eq!(tracker.get_medication("Vitamin D").unwrap().current_count, Quantity::whole(30));
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    assert_eq!(tracker.doses_taken("Aspirin", &today), 1);
+    assert_eq!(tracker.mark_group_taken("Morning packet", &today), Ok(0));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+#[test]
+fn interacting_pair_is_flagged() {
+    let mut tracker = common::tracker("interacting_pair_is_flagged");
+    common::add(&mut tracker, "Warfarin", "Evening", 30);
+    common::add(&mut tracker, "ASPIRIN low dose", "Morning", 30);
+    
+    assert_eq!(tracker.check_interactions(), vec![(
+        "ASPIRIN low dose".to_string(),
+        "Warfarin".to_string(),
+        "Increased risk of bleeding".to_string(),
+    )]);
+}
+
+#[test]
+fn brand_names_are_matched() {
+    let mut tracker = common::tracker("brand_names_are_matched");
+    common::add(&mut tracker, "Coumadin", "Evening", 30);
+    common::add(&mut tracker, "Advil", "As needed", 30);
+    
+    assert_eq!(tracker.check_interactions().len(), 1);
+}
+
+#[test]
+fn safe_medications_produce_no_warning() {
+    let mut tracker = common::tracker("safe_medications_produce_no_warning");
+    common::add(&mut tracker, "Metformin", "Evening", 30);
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    
+    assert!(tracker.check_interactions().is_empty());
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use medication_tracker::{get_today, localize_time_of_day, tr, Locale};
+
+#[test]
+fn spanish_reminders_keep_canonical_storage() {
+    let mut tracker = common::tracker("spanish_reminders_keep_canonical_storage");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.set_locale(Locale::Spanish);
+    let today = get_today();
+    
+    assert_eq!(tracker.active_reminders(&today, "08:30"), ["Lisinopril - Mañana"]);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().time_of_day, "Morning");
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().time_of_day, "Morning");
+    assert_eq!(tracker.active_reminders(&today, "08:30"), ["Lisinopril at Morning"]);
+}
+
+#[test]
+fn lookups_fall_back_to_english() {
+    assert_eq!(tr(Locale::Spanish, "TODAY"), "HOY");
+    assert_eq!(tr(Locale::English, "TODAY"), "TODAY");
+    assert_eq!(tr(Locale::Spanish, "Not in the catalog"), "Not in the catalog");
+    assert_eq!(localize_time_of_day(Locale::Spanish, "evening"), "Noche");
+    assert_eq!(localize_time_of_day(Locale::Spanish, "21:30"), "21:30");
+    assert_eq!("es".parse(), Ok(Locale::Spanish));
+    assert!("klingon".parse::<Locale>().is_err());
+}
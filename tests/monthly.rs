@@ -0,0 +1,56 @@
+mod common;
+
+fn calendar_rows(summary: &str) -> Vec<&str> {
+    summary.lines()
+        .skip_while(|line| !line.starts_with("CALENDAR"))
+        .skip(1)
+        .take(2)
+        .collect()
+}
+
+#[test]
+fn monthly_calendar_follows_the_week_start_day() {
+    let mut tracker = common::tracker("monthly_calendar_follows_the_week_start_day");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    // September 2024 starts on a Sunday.
+    let rows = calendar_rows(&tracker.generate_monthly_summary(2024, 9)).join("\n");
+    assert!(rows.starts_with("Mon       Tue"));
+    assert!(rows.contains(&format!("\n{} 1: 0/1", " ".repeat(60))));
+    
+    tracker.set_week_start_day("Sun".parse().unwrap()).unwrap();
+    let summary = tracker.generate_monthly_summary(2024, 9);
+    let rows = calendar_rows(&summary);
+    assert!(rows[0].starts_with("Sun       Mon"));
+    assert!(rows[0].trim_end().ends_with("Sat"));
+    assert!(rows[1].starts_with(" 1: 0/1"));
+    assert!(rows[1].contains(" 7: 0/1"));
+    assert!(!rows[1].contains(" 8: 0/1"));
+}
+
+#[test]
+fn monthly_percentage_counts_every_day_of_the_month() {
+    let mut tracker = common::tracker("monthly_percentage_counts_every_day_of_the_month");
+    common::add(&mut tracker, "Lisinopril", "Morning", 60);
+    let mut tracker = common::backdate(tracker, "2024-01-01");
+    for day in (1..=29).filter(|day| ![3, 14, 29].contains(day)) {
+        tracker.mark_taken("Lisinopril", &format!("2024-02-{:02}", day), 1, true).unwrap();
+    }
+    
+    let summary = tracker.generate_monthly_summary(2024, 2);
+    assert!(summary.contains("Month: February 2024"));
+    assert!(summary.contains("Lisinopril (10mg): 26/29 days (89.7%)"));
+    assert!(summary.contains("29: 0/1"));
+    assert!(!summary.contains("30: "));
+    assert!(tracker.generate_monthly_summary(2024, 13).starts_with("Invalid month"));
+}
+
+#[test]
+fn monthly_report_is_saved_in_the_data_directory() {
+    let mut tracker = common::tracker("monthly_report_is_saved_in_the_data_directory");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    let path = std::path::PathBuf::from(tracker.save_monthly_report_to_file(2024, 2).unwrap());
+    assert_eq!(path.parent(), Some(tracker.data_dir()));
+    assert!(std::fs::read_to_string(path).unwrap().contains("MONTHLY SUMMARY FOR Test Patient"));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use medication_tracker::{get_today, slugify, Quantity};
+use std::fs;
+
+#[test]
+fn batched_marks_reach_disk_on_save_all() {
+    let mut tracker = common::tracker("batched_marks_reach_disk_on_save_all");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    tracker.save_all().unwrap();
+    let meds_file = tracker.data_dir().join(format!("{}_meds.txt", slugify(tracker.patient_name())));
+    let saved = fs::read_to_string(&meds_file).unwrap();
+    let today = get_today();
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    tracker.mark_taken("Metformin", &today, 2, true).unwrap();
+    tracker.mark_taken("Metformin", &today, 2, false).unwrap();
+    assert_eq!(fs::read_to_string(&meds_file).unwrap(), saved);
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(59));
+    assert_eq!(tracker.doses_taken("Metformin", &today), 1);
+}
+
+#[test]
+fn immediate_persistence_writes_every_change() {
+    let mut tracker = common::tracker("immediate_persistence_writes_every_change");
+    tracker.set_immediate_persistence(true).unwrap();
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    assert!(!tracker.has_unsaved_changes());
+    
+    let meds_file = tracker.data_dir().join(format!("{}_meds.txt", slugify(tracker.patient_name())));
+    let saved = medication_tracker::Medication::try_from(fs::read_to_string(&meds_file).unwrap().trim()).unwrap();
+    assert_eq!(saved.current_count, Quantity::whole(29));
+}
+
+#[test]
+fn restore_brings_back_the_backed_up_state() {
+    let mut tracker = common::tracker("restore_brings_back_the_backed_up_state");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    let backups = tracker.data_dir().join("backups");
+    let backup_dir = tracker.backup(&backups).unwrap();
+    
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    tracker.mark_taken("Lisinopril", &today, 1, false).unwrap();
+    tracker.save_all().unwrap();
+    
+    let empty = backups.join("empty");
+    fs::create_dir_all(&empty).unwrap();
+    assert!(tracker.restore(&empty).is_err());
+    assert_eq!(tracker.medication_names(), ["Lisinopril", "Metformin"]);
+    
+    tracker.restore(&backup_dir).unwrap();
+    assert_eq!(tracker.medication_names(), ["Lisinopril"]);
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names(), ["Lisinopril"]);
+}
+
+#[test]
+fn archived_logs_leave_the_active_file() {
+    let mut tracker = common::tracker("archived_logs_leave_the_active_file");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    for date in ["2024-03-01", "2024-03-02", "2024-03-10"] {
+        tracker.mark_taken("Lisinopril", date, 1, true).unwrap();
+    }
+    
+    assert_eq!(tracker.archive_logs_before("2024-03-05").unwrap(), 2);
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-01"), 0);
+    assert_eq!(tracker.doses_taken("Lisinopril", "2024-03-10"), 1);
+    assert_eq!(tracker.archive_logs_before("2024-03-05").unwrap(), 0);
+    
+    let stem = slugify(tracker.patient_name());
+    let archive = fs::read_to_string(tracker.data_dir().join(format!("{}_logs_archive_2024-03-05.txt", stem))).unwrap();
+    assert!(archive.lines().any(|line| line.starts_with("2024-03-01,Lisinopril,1")));
+    assert!(archive.lines().any(|line| line.starts_with("2024-03-02,Lisinopril,1")));
+    assert!(!archive.contains("2024-03-10"));
+    let active = fs::read_to_string(tracker.data_dir().join(format!("{}_logs.txt", stem))).unwrap();
+    assert!(!active.contains("2024-03-01") && !active.contains("2024-03-02"));
+    assert!(active.contains("2024-03-10"));
+}
+
+#[test]
+fn dropping_the_tracker_saves_pending_changes() {
+    let mut tracker = common::tracker("dropping_the_tracker_saves_pending_changes");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    assert!(tracker.has_unsaved_changes());
+    
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    let tracker = medication_tracker::MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.doses_taken("Lisinopril", &get_today()), 1);
+}
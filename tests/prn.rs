@@ -0,0 +1,47 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, Medication};
+
+#[test]
+fn as_needed_medications_are_prn() {
+    let mut tracker = common::tracker("as_needed_medications_are_prn");
+    common::add(&mut tracker, "Ibuprofen", "As needed", 20);
+    let today = get_today();
+    
+    assert!(tracker.get_medication("Ibuprofen").unwrap().is_prn);
+    assert!(tracker.overdue_medications(&today, "23:59").is_empty());
+    assert!(tracker.active_reminders(&today, "23:59").is_empty());
+    assert!(tracker.set_prn("Ibuprofen", false).is_err());
+}
+
+#[test]
+fn rescheduling_follows_the_as_needed_bucket() {
+    let mut tracker = common::tracker("rescheduling_follows_the_as_needed_bucket");
+    common::add(&mut tracker, "Ibuprofen", "Morning", 20);
+    
+    tracker.edit_medication("Ibuprofen", None, Some("As needed".to_string())).unwrap();
+    assert!(tracker.get_medication("Ibuprofen").unwrap().is_prn);
+    tracker.edit_medication("Ibuprofen", None, Some("Evening".to_string())).unwrap();
+    assert!(!tracker.get_medication("Ibuprofen").unwrap().is_prn);
+}
+
+#[test]
+fn legacy_as_needed_rows_load_as_prn() {
+    let mut med = Medication::try_from("Ibuprofen,200mg,As needed,20,20").unwrap();
+    assert!(med.is_prn);
+    
+    med.is_prn = false;
+    assert!(Medication::try_from(med.to_csv_line().as_str()).unwrap().is_prn);
+}
+
+#[test]
+fn as_needed_days_are_blank_in_the_weekly_csv() {
+    let mut tracker = common::tracker("as_needed_days_are_blank_in_the_weekly_csv");
+    common::add(&mut tracker, "Ibuprofen", "As needed", 20);
+    let monday = add_days(&tracker.week_start(&get_today()), -7).unwrap();
+    let mut tracker = common::backdate(tracker, &add_days(&monday, -7).unwrap());
+    tracker.mark_taken("Ibuprofen", &add_days(&monday, 2).unwrap(), 1, true).unwrap();
+    
+    let csv = std::fs::read_to_string(tracker.export_weekly_csv(&monday).unwrap()).unwrap();
+    assert_eq!(csv.lines().nth(1), Some("Ibuprofen,,,1,,,,,N/A,19"));
+}
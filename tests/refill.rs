@@ -0,0 +1,80 @@
+mod common;
+
+use medication_tracker::{get_today, Quantity};
+
+#[test]
+fn refill_above_the_cap_is_rejected() {
+    let mut tracker = common::tracker("refill_above_the_cap_is_rejected");
+    common::add(&mut tracker, "Lisinopril", "Morning", 20);
+    tracker.set_max_count("Lisinopril", Some(30)).unwrap();
+    
+    assert!(tracker.refill_medication("Lisinopril", Quantity::whole(11)).unwrap_err().contains("above the maximum of 30"));
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(20));
+    tracker.refill_medication("Lisinopril", Quantity::whole(10)).unwrap();
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+}
+
+#[test]
+fn zero_refill_is_explained() {
+    let mut tracker = common::tracker("zero_refill_is_explained");
+    common::add(&mut tracker, "Lisinopril", "Morning", 20);
+    
+    assert_eq!(tracker.refill_medication("Lisinopril", Quantity::ZERO), Err("Refill amount must be greater than zero".to_string()));
+    assert!(tracker.refill_medication("Aspirin", Quantity::whole(5)).is_err());
+}
+
+#[test]
+fn supply_warnings_put_the_most_urgent_first() {
+    let mut tracker = common::tracker("supply_warnings_put_the_most_urgent_first");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Lisinopril", "Morning", 5);
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 6, 2, String::new()).unwrap();
+    
+    assert_eq!(tracker.supply_warnings(), [
+        "Metformin: 6 left, about 3 days of supply",
+        "Lisinopril: 5 left, about 5 days of supply",
+    ]);
+}
+
+#[test]
+fn standard_refills_count_down_to_the_contact_doctor_warning() {
+    let mut tracker = common::tracker("standard_refills_count_down_to_the_contact_doctor_warning");
+    common::add(&mut tracker, "Metformin", "Evening", 5);
+    common::add(&mut tracker, "Aspirin", "Morning", 5);
+    assert!(tracker.refills_left("Metformin").is_err());
+    tracker.set_refills("Metformin", 30, 1).unwrap();
+    assert!(tracker.refill_warnings().is_empty());
+    
+    tracker.refill_medication("Metformin", Quantity::whole(10)).unwrap();
+    assert_eq!(tracker.refills_left("Metformin"), Ok(1));
+    tracker.refill_medication("Metformin", Quantity::whole(30)).unwrap();
+    assert_eq!(tracker.refills_left("Metformin"), Ok(0));
+    assert!(tracker.refill_warnings().is_empty());
+    
+    tracker.set_refills("Aspirin", 30, 0).unwrap();
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.refills_left("Metformin"), Ok(0));
+    assert_eq!(tracker.refill_warnings(), ["Aspirin: no refills left — contact doctor"]);
+}
+
+#[test]
+fn refill_all_tops_every_medication_back_up() {
+    let mut tracker = common::tracker("refill_all_tops_every_medication_back_up");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Aspirin", "Morning", 90);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    tracker.refill_medication("Metformin", Quantity::whole(5)).unwrap();
+    
+    assert_eq!(tracker.refill_all(true, None), Ok(2));
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(65));
+    assert_eq!(tracker.get_medication("Aspirin").unwrap().current_count, Quantity::whole(90));
+    assert_eq!(tracker.refill_all(true, None), Ok(0));
+    
+    assert_eq!(tracker.refill_all(false, Some(10)), Ok(3));
+    assert_eq!(tracker.get_medication("Aspirin").unwrap().current_count, Quantity::whole(100));
+    assert!(tracker.refill_all(false, None).is_err());
+}
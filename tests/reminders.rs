@@ -0,0 +1,165 @@
+mod common;
+
+use medication_tracker::{get_today, ReminderSink};
+use std::cell::RefCell;
+
+#[test]
+fn removing_a_medication_clears_its_reminder_state() {
+    let mut tracker = common::tracker("removing_a_medication_clears_its_reminder_state");
+    common::add(&mut tracker, "Lisinopril", "08:00", 30);
+    let today = get_today();
+    for _ in 0..3 {
+        tracker.record_reminders_shown(&today, "08:30").unwrap();
+    }
+    assert_eq!(tracker.reminder_level("Lisinopril", &today), 3);
+    
+    tracker.remove_medication("Lisinopril").unwrap();
+    common::add(&mut tracker, "Lisinopril", "08:00", 30);
+    assert_eq!(tracker.reminder_level("Lisinopril", &today), 1);
+    
+    tracker.acknowledge_reminder("Lisinopril", &today).unwrap();
+    tracker.remove_medication("Lisinopril").unwrap();
+    common::add(&mut tracker, "Lisinopril", "08:00", 30);
+    for _ in 0..2 {
+        tracker.record_reminders_shown(&today, "08:30").unwrap();
+    }
+    assert_eq!(tracker.reminder_level("Lisinopril", &today), 2);
+}
+
+#[test]
+fn snoozed_reminders_stay_hidden_until_the_snooze_time() {
+    let mut tracker = common::tracker("snoozed_reminders_stay_hidden_until_the_snooze_time");
+    common::add(&mut tracker, "Lisinopril", "08:00", 30);
+    let today = get_today();
+    assert_eq!(tracker.active_reminders(&today, "08:30").len(), 1);
+    
+    // Only a snooze that hasn't passed on the real clock is active, so use the ends of the day.
+    tracker.snooze_reminder("Lisinopril", &today, "23:59").unwrap();
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.today_status(&today).unwrap()[0].snoozed_until.as_deref(), Some("23:59"));
+    assert!(tracker.active_reminders(&today, "08:30").is_empty());
+    
+    let mut tracker = tracker;
+    tracker.snooze_reminder("Lisinopril", &today, "00:00").unwrap();
+    assert_eq!(tracker.today_status(&today).unwrap()[0].snoozed_until, None);
+    assert_eq!(tracker.active_reminders(&today, "08:30").len(), 1);
+    assert!(tracker.snooze_reminder("Lisinopril", &today, "Evening").is_err());
+}
+
+#[test]
+fn reminders_follow_the_clock() {
+    let mut tracker = common::tracker("reminders_follow_the_clock");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Atorvastatin", "Evening", 30);
+    let today = get_today();
+    
+    assert!(tracker.active_reminders(&today, "07:00").is_empty());
+    assert_eq!(tracker.upcoming_medications(&today, "07:00").len(), 2);
+    
+    let overdue = tracker.overdue_medications(&today, "12:00");
+    assert_eq!(overdue.len(), 1);
+    assert!(overdue[0].contains("Lisinopril"));
+    let upcoming = tracker.upcoming_medications(&today, "12:00");
+    assert_eq!(upcoming.len(), 1);
+    assert!(upcoming[0].contains("Atorvastatin"));
+    
+    let due = tracker.due_medications(&today, "18:30");
+    assert_eq!(due.len(), 1);
+    assert!(due[0].contains("Atorvastatin"));
+    assert_eq!(tracker.overdue_medications(&today, "19:00").len(), 2);
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    assert!(tracker.overdue_medications(&today, "12:00").is_empty());
+}
+
+#[test]
+fn next_dose_is_the_nearest_one_still_to_come() {
+    let mut tracker = common::tracker("next_dose_is_the_nearest_one_still_to_come");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let today = get_today();
+    
+    assert_eq!(tracker.next_dose(&today, "12:00"), Some(("Metformin".to_string(), "18:00".to_string())));
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    assert_eq!(tracker.next_dose(&today, "12:00").map(|(name, _)| name).as_deref(), Some("Atorvastatin"));
+    tracker.mark_taken("Atorvastatin", &today, 1, true).unwrap();
+    assert_eq!(tracker.next_dose(&today, "12:00"), None);
+    assert_eq!(tracker.next_dose(&today, "noon"), None);
+}
+
+#[test]
+fn grace_window_decides_due_or_overdue() {
+    let mut tracker = common::tracker("grace_window_decides_due_or_overdue");
+    common::add(&mut tracker, "Lisinopril", "08:00", 30);
+    tracker.set_grace_minutes("Lisinopril", 30).unwrap();
+    let today = get_today();
+    
+    assert_eq!(tracker.due_medications(&today, "08:29").len(), 1);
+    assert!(tracker.overdue_medications(&today, "08:29").is_empty());
+    assert!(tracker.due_medications(&today, "08:30").is_empty());
+    assert_eq!(tracker.overdue_medications(&today, "08:30").len(), 1);
+    
+    let mut tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().grace_minutes, 30);
+    assert!(tracker.set_grace_minutes("Lisinopril", 24 * 60 + 1).is_err());
+}
+
+#[test]
+fn only_reminders_for_the_current_part_of_the_day_show() {
+    let mut tracker = common::tracker("only_reminders_for_the_current_part_of_the_day_show");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Melatonin", "Bedtime", 30);
+    let today = get_today();
+    
+    let morning = tracker.active_reminder_entries(&today, "09:00");
+    assert_eq!(morning.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["Lisinopril"]);
+    let night = tracker.active_reminder_entries(&today, "22:30");
+    assert_eq!(night.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["Melatonin"]);
+}
+
+#[test]
+fn due_soon_looks_ahead_within_the_window() {
+    let mut tracker = common::tracker("due_soon_looks_ahead_within_the_window");
+    common::add(&mut tracker, "Lisinopril", "08:30", 30);
+    common::add(&mut tracker, "Aspirin", "08:00", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Melatonin", "00:15", 30);
+    let today = get_today();
+    
+    let soon = tracker.due_soon(&today, "08:00", 60);
+    assert_eq!(soon.len(), 1);
+    assert!(soon[0].starts_with("Lisinopril"));
+    assert_eq!(tracker.due_soon(&today, "17:30", 60).len(), 1);
+    assert!(tracker.due_soon(&today, "23:45", 60).is_empty());
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    assert!(tracker.due_soon(&today, "08:00", 60).is_empty());
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    sent: RefCell<Vec<(String, String)>>,
+}
+
+impl ReminderSink for RecordingSink {
+    fn notify(&self, med: &str, message: &str) {
+        self.sent.borrow_mut().push((med.to_string(), message.to_string()));
+    }
+}
+
+#[test]
+fn each_missed_medication_reaches_the_sink() {
+    let mut tracker = common::tracker("each_missed_medication_reaches_the_sink");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Vitamin D", "Morning", 30);
+    let today = get_today();
+    tracker.mark_taken("Vitamin D", &today, 1, true).unwrap();
+    
+    let sink = RecordingSink::default();
+    assert_eq!(tracker.notify_reminders(&today, "12:00", &sink), 2);
+    let sent = sink.sent.into_inner();
+    assert_eq!(sent.iter().map(|(med, _)| med.as_str()).collect::<Vec<_>>(), ["Aspirin", "Lisinopril"]);
+    assert!(sent.iter().all(|(med, message)| message.contains(med.as_str())));
+}
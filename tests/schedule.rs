@@ -0,0 +1,104 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, parse_schedule, Frequency, Medication, MedicationTracker, Priority, Schedule};
+
+#[test]
+fn valid_schedules_parse() {
+    assert_eq!(parse_schedule("08:30"), Ok(Schedule::Clock(8, 30)));
+    assert_eq!(parse_schedule(" 0:05 "), Ok(Schedule::Clock(0, 5)));
+    assert_eq!(parse_schedule("23:59"), Ok(Schedule::Clock(23, 59)));
+    assert_eq!(parse_schedule("bedtime"), Ok(Schedule::Named("Bedtime".to_string())));
+    assert_eq!(parse_schedule("As needed"), Ok(Schedule::Named("As needed".to_string())));
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+    for input in ["24:00", "12:60", "ab:30", "12:", ":30", "noon", ""] {
+        assert!(parse_schedule(input).is_err(), "{} should be rejected", input);
+    }
+}
+
+#[test]
+fn status_is_sorted_by_clock_time() {
+    let mut tracker = common::tracker("status_is_sorted_by_clock_time");
+    common::add(&mut tracker, "Aspirin", "21:15", 30);
+    common::add(&mut tracker, "Zinc", "07:45", 30);
+    common::add(&mut tracker, "Metformin", "Afternoon", 30);
+    common::add(&mut tracker, "Biotin", "09:00", 30);
+    
+    let names: Vec<String> = tracker.check_today_status(&get_today()).unwrap().into_iter().map(|entry| entry.0).collect();
+    assert_eq!(names, ["Zinc", "Biotin", "Metformin", "Aspirin"]);
+}
+
+#[test]
+fn morning_sorts_before_evening_whatever_the_dosage() {
+    let mut tracker = common::tracker("morning_sorts_before_evening_whatever_the_dosage");
+    tracker.add_medication("Aspirin".to_string(), "1 tablet".to_string(), "Evening".to_string(), 30, 1, String::new()).unwrap();
+    tracker.add_medication("Zinc".to_string(), "50mg".to_string(), "Morning".to_string(), 30, 1, String::new()).unwrap();
+    tracker.add_medication("Biotin".to_string(), "A capsule".to_string(), "Bedtime".to_string(), 30, 1, String::new()).unwrap();
+    tracker.add_medication("Calcium".to_string(), "600mg".to_string(), "Morning".to_string(), 30, 1, String::new()).unwrap();
+    
+    let names: Vec<String> = tracker.check_today_status(&get_today()).unwrap().into_iter().map(|entry| entry.0).collect();
+    assert_eq!(names, ["Calcium", "Zinc", "Aspirin", "Biotin"]);
+}
+
+#[test]
+fn high_priority_comes_first_and_is_flagged() {
+    let mut tracker = common::tracker("high_priority_comes_first_and_is_flagged");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Warfarin", "Evening", 30);
+    common::add(&mut tracker, "Biotin", "Morning", 30);
+    tracker.set_priority("Warfarin", Priority::High).unwrap();
+    tracker.set_priority("Aspirin", Priority::Low).unwrap();
+    let today = get_today();
+    
+    let names: Vec<String> = tracker.check_today_status(&today).unwrap().into_iter().map(|entry| entry.0).collect();
+    assert_eq!(names, ["Warfarin", "Biotin", "Aspirin"]);
+    let listed = tracker.list_medications(&today);
+    assert!(listed[0].contains("Warfarin"));
+    assert!(listed[2].contains("Aspirin"));
+    assert!(tracker.overdue_medications(&today, "23:00").iter().any(|reminder| reminder.starts_with("CRITICAL: Warfarin")));
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Warfarin").unwrap().priority, Priority::High);
+    assert_eq!(tracker.get_medication("Biotin").unwrap().priority, Priority::Normal);
+}
+
+#[test]
+fn status_order_follows_adds_edits_and_removals() {
+    let mut tracker = common::tracker("status_order_follows_adds_edits_and_removals");
+    common::add(&mut tracker, "Aspirin", "Bedtime", 30);
+    common::add(&mut tracker, "Zinc", "Morning", 30);
+    let today = get_today();
+    let names = |tracker: &MedicationTracker| -> Vec<String> {
+        tracker.check_today_status(&today).unwrap().into_iter().map(|entry| entry.0).collect()
+    };
+    assert_eq!(names(&tracker), ["Zinc", "Aspirin"]);
+    
+    common::add(&mut tracker, "Biotin", "06:30", 30);
+    assert_eq!(names(&tracker), ["Biotin", "Zinc", "Aspirin"]);
+    tracker.edit_medication("Aspirin", None, Some("Morning".to_string())).unwrap();
+    assert_eq!(names(&tracker), ["Biotin", "Aspirin", "Zinc"]);
+    tracker.remove_medication("Biotin").unwrap();
+    assert_eq!(names(&tracker), ["Aspirin", "Zinc"]);
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(names(&tracker), ["Aspirin", "Zinc"]);
+}
+
+#[test]
+fn every_n_days_counts_from_its_anchor() {
+    let mut tracker = common::tracker("every_n_days_counts_from_its_anchor");
+    common::add(&mut tracker, "Vitamin B12", "Morning", 30);
+    tracker.set_frequency("Vitamin B12", Frequency::EveryNDays(3)).unwrap();
+    let today = get_today();
+    let day = |offset| add_days(&today, offset).unwrap();
+    
+    let med = tracker.get_medication("Vitamin B12").unwrap();
+    assert_eq!(med.interval_start.as_deref(), Some(today.as_str()));
+    assert!(med.is_due_on(&today) && !med.is_due_on(&day(1)) && !med.is_due_on(&day(2)) && med.is_due_on(&day(3)));
+    
+    let legacy = Medication::try_from("Vitamin B12,1000mcg,Morning,30,30,1,7,,,,Normal,0,Every 3 days").unwrap();
+    assert_eq!(legacy.interval_start.as_deref(), Some(today.as_str()));
+    assert!(!legacy.is_due_on(&day(1)));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use medication_tracker::{Medication, MedicationTracker};
+
+fn names(meds: Vec<&Medication>) -> Vec<&str> {
+    meds.into_iter().map(|med| med.name.as_str()).collect()
+}
+
+fn sample(test_name: &str) -> MedicationTracker {
+    let mut tracker = common::tracker(test_name);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Metoprolol", "Morning", 30);
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker
+}
+
+#[test]
+fn partial_names_match_case_insensitively() {
+    let tracker = sample("partial_names_match_case_insensitively");
+    
+    assert_eq!(names(tracker.find_medications("  MET ")), ["Metformin", "Metoprolol"]);
+    assert_eq!(names(tracker.find_medications("evening")), ["Metformin"]);
+    assert_eq!(tracker.find_medications("").len(), 3);
+    assert!(tracker.find_medications("aspirin").is_empty());
+}
+
+#[test]
+fn time_of_day_filter() {
+    let tracker = sample("time_of_day_filter");
+    
+    assert_eq!(names(tracker.medications_at_time("morning")), ["Lisinopril", "Metoprolol"]);
+    assert!(tracker.medications_at_time("Bedtime").is_empty());
+}
+
+#[test]
+fn typed_names_resolve_exactly_by_prefix_or_with_a_suggestion() {
+    let tracker = sample("typed_names_resolve_exactly_by_prefix_or_with_a_suggestion");
+    
+    assert_eq!(tracker.resolve_medication(" metformin "), Ok("Metformin".to_string()));
+    assert_eq!(tracker.resolve_medication("lis"), Ok("Lisinopril".to_string()));
+    assert_eq!(tracker.resolve_medication("Met"), Err("'Met' matches several medications: Metformin, Metoprolol".to_string()));
+    assert_eq!(tracker.resolve_medication("Lisinoprl"), Err("Did you mean 'Lisinopril'?".to_string()));
+    assert_eq!(tracker.resolve_medication("Aspirin"), Err("No medication matches 'Aspirin'".to_string()));
+    assert!(tracker.resolve_medication("  ").is_err());
+}
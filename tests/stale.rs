@@ -0,0 +1,24 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, STALE_AFTER_DAYS};
+
+#[test]
+fn medications_with_no_recent_activity_are_stale() {
+    let mut tracker = common::tracker("medications_with_no_recent_activity_are_stale");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 30);
+    let today = get_today();
+    let mut tracker = common::backdate(tracker, &add_days(&today, -10).unwrap());
+    tracker.mark_taken("Metformin", &add_days(&today, -2).unwrap(), 1, false).unwrap();
+    
+    assert_eq!(tracker.stale_medications(&today, STALE_AFTER_DAYS), vec!["Lisinopril".to_string()]);
+    assert!(tracker.stale_medications(&today, 0).is_empty());
+}
+
+#[test]
+fn newly_added_medications_are_not_stale() {
+    let mut tracker = common::tracker("newly_added_medications_are_not_stale");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    assert!(tracker.stale_medications(&get_today(), STALE_AFTER_DAYS).is_empty());
+}
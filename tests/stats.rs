@@ -0,0 +1,61 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+
+#[test]
+fn lifetime_stats_count_tracked_medications_only() {
+    let mut tracker = common::tracker("lifetime_stats_count_tracked_medications_only");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 30);
+    let today = get_today();
+    let yesterday = add_days(&today, -1).unwrap();
+    let mut tracker = common::backdate(tracker, &yesterday);
+    tracker.mark_taken("Lisinopril", &yesterday, 1, true).unwrap();
+    tracker.mark_taken("Lisinopril", &today, 1, false).unwrap();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    tracker.remove_medication("Metformin").unwrap();
+    
+    let stats = tracker.lifetime_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats["Lisinopril"], (1, 1));
+}
+
+#[test]
+fn daily_digest_is_one_line() {
+    let mut tracker = common::tracker("daily_digest_is_one_line");
+    assert_eq!(tracker.daily_digest("2024-03-02").unwrap(), "Test Patient 2024-03-02: no medications scheduled");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 30);
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    for name in ["Lisinopril", "Aspirin", "Atorvastatin"] {
+        tracker.mark_taken(name, "2024-03-02", 1, true).unwrap();
+    }
+    
+    assert_eq!(tracker.daily_digest("2024-03-02").unwrap(), "Test Patient 2024-03-02: 3/4 taken, missed Metformin (Evening)");
+    tracker.mark_taken("Metformin", "2024-03-02", 1, true).unwrap();
+    assert_eq!(tracker.daily_digest("2024-03-02").unwrap(), "Test Patient 2024-03-02: all 4 taken, great job!");
+}
+
+#[test]
+fn clinical_report_has_header_percentages_clusters_and_supply() {
+    let mut tracker = common::tracker("clinical_report_has_header_percentages_clusters_and_supply");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let mut tracker = common::backdate(tracker, "2024-03-01");
+    for date in ["2024-03-01", "2024-03-02", "2024-03-03", "2024-03-04"] {
+        tracker.mark_taken("Lisinopril", date, 1, true).unwrap();
+    }
+    tracker.mark_taken("Metformin", "2024-03-01", 1, true).unwrap();
+    
+    let report = tracker.generate_clinical_report("2024-03-01", "2024-03-04");
+    assert!(report.starts_with("MEDICATION ADHERENCE REPORT\nPatient: Test Patient\nReporting period: 2024-03-01 to 2024-03-04\n"));
+    assert!(report.contains("  Lisinopril 10mg: 100.0%\n"));
+    assert!(report.contains("  Metformin 10mg: 25.0%\n"));
+    assert!(report.contains("  Metformin: 2024-03-02 to 2024-03-04 (3 days)\n"));
+    assert!(!report.contains("  Lisinopril: 2024"));
+    assert!(report.contains("  Lisinopril: 26 left, about 26 days\n"));
+    assert!(report.contains("  Metformin: 59 left, about 59 days\n"));
+    assert!(tracker.generate_clinical_report("2024-03-04", "2024-03-01").starts_with("Invalid reporting period"));
+}
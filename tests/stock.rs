@@ -0,0 +1,128 @@
+mod common;
+
+use medication_tracker::{get_today, MarkOutcome, Medication, Quantity};
+
+#[test]
+fn unmarking_an_out_of_stock_dose_restores_nothing() {
+    let mut tracker = common::tracker("unmarking_an_out_of_stock_dose_restores_nothing");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Morning".to_string(), 1, 2, String::new()).unwrap();
+    let today = get_today();
+    
+    assert_eq!(tracker.mark_taken("Metformin", &today, 1, true).unwrap(), MarkOutcome::Recorded);
+    assert_eq!(tracker.mark_taken("Metformin", &today, 2, true).unwrap(), MarkOutcome::OutOfStock);
+    tracker.mark_taken("Metformin", &today, 2, false).unwrap();
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::ZERO);
+    
+    tracker.mark_taken("Metformin", &today, 1, false).unwrap();
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(1));
+}
+
+#[test]
+fn deduction_flag_survives_reopening() {
+    let mut tracker = common::tracker("deduction_flag_survives_reopening");
+    common::add(&mut tracker, "Atorvastatin", "Evening", 0);
+    let today = get_today();
+    
+    assert_eq!(tracker.mark_taken("Atorvastatin", &today, 1, true).unwrap(), MarkOutcome::OutOfStock);
+    let mut tracker = common::reopen(tracker);
+    tracker.refill_medication("Atorvastatin", Quantity::whole(5)).unwrap();
+    tracker.mark_taken("Atorvastatin", &today, 1, false).unwrap();
+    assert_eq!(tracker.get_medication("Atorvastatin").unwrap().current_count, Quantity::whole(5));
+}
+
+#[test]
+fn reset_day_restores_only_deducted_doses() {
+    let mut tracker = common::tracker("reset_day_restores_only_deducted_doses");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Morning".to_string(), 1, 2, String::new()).unwrap();
+    let today = get_today();
+    
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    assert_eq!(tracker.mark_taken("Metformin", &today, 2, true).unwrap(), MarkOutcome::OutOfStock);
+    assert_eq!(tracker.reset_day(&today).unwrap(), 2);
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(1));
+}
+
+#[test]
+fn taking_a_dose_can_cross_the_refill_threshold() {
+    let mut tracker = common::tracker("taking_a_dose_can_cross_the_refill_threshold");
+    common::add(&mut tracker, "Metformin", "Evening", 8);
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    assert!(tracker.low_stock_medications().is_empty());
+    
+    tracker.mark_taken("Metformin", &get_today(), 1, true).unwrap();
+    assert_eq!(tracker.low_stock_medications(), vec![("Metformin".to_string(), Quantity::whole(7))]);
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Metformin").unwrap().refill_threshold, 7);
+    assert_eq!(tracker.low_stock_medications().len(), 1);
+}
+
+#[test]
+fn old_records_get_the_default_refill_threshold() {
+    let med = Medication::try_from("Metformin,500mg,Evening,5,60").unwrap();
+    assert_eq!(med.refill_threshold, 7);
+}
+
+#[test]
+fn taken_then_missed_returns_the_count() {
+    let mut tracker = common::tracker("taken_then_missed_returns_the_count");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    
+    for _ in 0..3 {
+        tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+        tracker.mark_taken("Lisinopril", &today, 1, false).unwrap();
+    }
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 0);
+}
+
+#[test]
+fn marking_taken_twice_deducts_once() {
+    let mut tracker = common::tracker("marking_taken_twice_deducts_once");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    
+    tracker.mark_taken("Lisinopril", &today, 1, false).unwrap();
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+}
+
+#[test]
+fn ten_pills_once_daily_run_out_in_ten_days() {
+    let mut tracker = common::tracker("ten_pills_once_daily_run_out_in_ten_days");
+    common::add(&mut tracker, "Lisinopril", "Morning", 10);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 0);
+    common::add(&mut tracker, "Ibuprofen", "As needed", 20);
+    
+    assert_eq!(tracker.days_until_empty("Lisinopril"), Ok(10));
+    assert_eq!(tracker.run_out_date("Lisinopril", "2024-02-25").as_deref(), Ok("2024-03-06"));
+    assert_eq!(tracker.run_out_date("Atorvastatin", "2024-02-25").as_deref(), Ok("2024-02-25"));
+    assert!(tracker.run_out_date("Ibuprofen", "2024-02-25").is_err());
+    assert!(tracker.list_medications("2024-02-25").iter().any(|line| line.contains("runs out 2024-03-06")));
+}
+
+#[test]
+fn three_half_doses_deduct_one_and_a_half() {
+    let mut tracker = common::tracker("three_half_doses_deduct_one_and_a_half");
+    tracker.add_medication("Metoprolol".to_string(), "25mg".to_string(), "Morning".to_string(), 5, 3, String::new()).unwrap();
+    tracker.set_units_per_dose("Metoprolol", "0.5".parse().unwrap()).unwrap();
+    let today = get_today();
+    for dose in 1..=3 {
+        tracker.mark_taken("Metoprolol", &today, dose, true).unwrap();
+    }
+    
+    let med = tracker.get_medication("Metoprolol").unwrap();
+    assert_eq!(med.current_count, "3.5".parse().unwrap());
+    assert!(med.to_string().contains("(3.5 left)"));
+    tracker.refill_medication("Metoprolol", "0.25".parse().unwrap()).unwrap();
+    assert_eq!(tracker.get_medication("Metoprolol").unwrap().current_count.to_string(), "3.75");
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Metoprolol").unwrap().current_count.to_string(), "3.75");
+    assert!("0.125".parse::<Quantity>().is_err());
+}
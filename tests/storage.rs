@@ -0,0 +1,189 @@
+mod common;
+
+use medication_tracker::{get_today, get_week_start, slugify, Medication, MedicationTracker};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn commas_and_quotes_survive_reloading() {
+    let mut tracker = common::tracker("commas_and_quotes_survive_reloading");
+    tracker.add_medication(
+        "Calcium, Vitamin D".to_string(),
+        "1,000 mg".to_string(),
+        "Morning".to_string(),
+        30,
+        1,
+        "take with \"plenty\" of water, not milk".to_string(),
+    ).unwrap();
+    tracker.mark_taken("Calcium, Vitamin D", &get_today(), 1, true).unwrap();
+    
+    let tracker = common::reopen(tracker);
+    let med = tracker.get_medication("Calcium, Vitamin D").unwrap();
+    assert_eq!(med.dosage, "1,000 mg");
+    assert_eq!(med.notes, "take with \"plenty\" of water, not milk");
+    assert_eq!(tracker.doses_taken("Calcium, Vitamin D", &get_today()), 1);
+    assert!(tracker.load_warnings().is_empty());
+}
+
+#[test]
+fn leading_and_trailing_spaces_survive_reloading() {
+    let mut tracker = common::tracker("leading_and_trailing_spaces_survive_reloading");
+    tracker.add_medication(" Aspirin, low dose ".to_string(), " 81mg ".to_string(), "Evening".to_string(), 90, 1, String::new()).unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names(), vec![" Aspirin, low dose ".to_string()]);
+    assert_eq!(tracker.get_medication(" Aspirin, low dose ").unwrap().dosage, " 81mg ");
+}
+
+#[test]
+fn json_storage_round_trips() {
+    let mut tracker = common::tracker("json_storage_round_trips");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, "with food".to_string()).unwrap();
+    let today = get_today();
+    tracker.mark_taken_by("Lisinopril", &today, 1, "Sam").unwrap();
+    tracker.skip_dose("Metformin", &today, 2, "nausea").unwrap();
+    tracker.enable_json_storage().unwrap();
+    let before: Vec<String> = tracker.medication_names().iter()
+        .map(|name| tracker.get_medication(name).unwrap().to_csv_line())
+        .collect();
+    
+    let tracker = common::reopen(tracker);
+    assert!(tracker.uses_json_storage());
+    let after: Vec<String> = tracker.medication_names().iter()
+        .map(|name| tracker.get_medication(name).unwrap().to_csv_line())
+        .collect();
+    assert_eq!(after, before);
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    assert_eq!(tracker.doses_skipped("Metformin", &today), vec!["nausea".to_string()]);
+}
+
+#[test]
+fn notes_survive_reloading_and_old_rows_load_without_them() {
+    let mut tracker = common::tracker("notes_survive_reloading_and_old_rows_load_without_them");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 1, "take with food".to_string()).unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.get_medication("Metformin").unwrap().notes, "take with food");
+    assert!(tracker.list_medications(&get_today())[0].contains("take with food"));
+    assert!(tracker.check_today_status(&get_today()).unwrap()[0].1.contains("take with food"));
+    assert_eq!(Medication::try_from("Aspirin,81mg,Morning,30,30").unwrap().notes, "");
+}
+
+#[test]
+fn files_go_in_the_data_directory_only() {
+    let dir = common::data_dir("files_go_in_the_data_directory_only").join("nested");
+    let mut tracker = MedicationTracker::with_data_dir("Data Dir Patient", &dir).unwrap();
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    let report = tracker.save_chart_to_file(&get_week_start(), false).unwrap();
+    
+    assert!(Path::new(&report).starts_with(&dir));
+    assert!(dir.join("data_dir_patient_meds.txt").exists());
+    assert!(dir.join("data_dir_patient_logs.txt").exists());
+    assert!(!fs::read_dir(".").unwrap().any(|entry| entry.unwrap().file_name().to_string_lossy().starts_with("data_dir_patient")));
+    assert!(tracker.save_chart_to_file(&get_week_start(), false).is_err());
+}
+
+#[test]
+fn saving_a_report_twice_needs_overwrite() {
+    let mut tracker = common::tracker("saving_a_report_twice_needs_overwrite");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let week = get_week_start();
+    let report = tracker.save_chart_to_file(&week, false).unwrap();
+    let first = fs::read_to_string(&report).unwrap();
+    
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    assert!(tracker.save_chart_to_file(&week, false).unwrap_err().contains("already exists"));
+    assert_eq!(fs::read_to_string(&report).unwrap(), first);
+    
+    assert_eq!(tracker.save_chart_to_file(&week, true).unwrap(), report);
+    assert!(fs::read_to_string(&report).unwrap().contains("MEDICATION: Metformin"));
+}
+
+#[test]
+fn unsafe_patient_names_get_safe_distinct_files() {
+    assert_eq!(slugify("  Mary-Jane O'Brien  "), "mary_jane_o_brien");
+    assert_eq!(slugify("../../etc"), "etc");
+    assert_eq!(slugify("!!!"), "patient");
+    
+    let dir = common::data_dir("unsafe_patient_names_get_safe_distinct_files");
+    let mut tracker = MedicationTracker::with_data_dir("Mary-Jane O'Brien", &dir).unwrap();
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    drop(tracker);
+    let mut lookalike = MedicationTracker::with_data_dir("mary jane o brien", &dir).unwrap();
+    common::add(&mut lookalike, "Metformin", "Evening", 60);
+    lookalike.save_all().unwrap();
+    drop(lookalike);
+    
+    assert!(dir.join("mary_jane_o_brien_meds.txt").exists());
+    assert!(dir.join("mary_jane_o_brien_2_meds.txt").exists());
+    let tracker = MedicationTracker::with_data_dir("Mary-Jane O'Brien", &dir).unwrap();
+    assert_eq!(tracker.patient_name(), "Mary-Jane O'Brien");
+    assert_eq!(tracker.medication_names(), ["Lisinopril"]);
+    drop(tracker);
+    let lookalike = MedicationTracker::with_data_dir("mary jane o brien", &dir).unwrap();
+    assert_eq!(lookalike.medication_names(), ["Metformin"]);
+}
+
+#[test]
+fn crlf_files_with_blank_lines_load_fully() {
+    let mut tracker = common::tracker("crlf_files_with_blank_lines_load_fully");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    drop(tracker);
+    
+    for suffix in ["meds", "logs"] {
+        let path = dir.join(format!("{}_{}.txt", slugify("Test Patient"), suffix));
+        let contents = fs::read_to_string(&path).unwrap();
+        let windows: String = contents.lines().map(|line| format!("{}\r\n\r\n", line)).collect();
+        fs::write(&path, format!("{}\r\n", windows)).unwrap();
+    }
+    
+    let tracker = MedicationTracker::with_data_dir("Test Patient", &dir).unwrap();
+    assert_eq!(tracker.medication_names(), ["Lisinopril", "Metformin"]);
+    assert_eq!(tracker.get_medication("Metformin").unwrap().time_of_day, "Evening");
+    assert_eq!(tracker.doses_taken("Lisinopril", &get_today()), 1);
+    assert!(tracker.load_warnings().is_empty());
+}
+
+#[test]
+fn csv_import_adds_valid_rows_and_reports_bad_ones() {
+    let mut tracker = common::tracker("csv_import_adds_valid_rows_and_reports_bad_ones");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let path = tracker.data_dir().join("import.csv");
+    fs::write(&path, "name,dosage,time_of_day,count\r\n\"Vitamin D, 1000 IU\",1 capsule,morning,90\r\nMetformin,500mg,noon,60\r\nLisinopril,10mg,Morning,30\r\n\r\n").unwrap();
+    
+    assert_eq!(tracker.import_medications_csv(&path), Ok(1));
+    assert_eq!(tracker.medication_names(), ["Lisinopril", "Vitamin D, 1000 IU"]);
+    assert_eq!(tracker.get_medication("Vitamin D, 1000 IU").unwrap().time_of_day, "Morning");
+    let errors = tracker.import_errors();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("Line 3: "));
+    assert!(errors[1].starts_with("Line 4: "));
+    
+    fs::write(&path, "medication,amount\nAspirin,81mg\n").unwrap();
+    assert!(tracker.import_medications_csv(&path).is_err());
+}
+
+#[test]
+fn csv_line_round_trips_special_characters() {
+    let mut med = Medication::try_from("Aspirin,81mg,Morning,28,30").unwrap();
+    med.name = "Calcium, \"Extra\" Strength".to_string();
+    med.dosage = " 1,000 mg ".to_string();
+    med.notes = "Don't take with milk, \"ever\"".to_string();
+    
+    let line = med.to_csv_line();
+    let parsed = Medication::try_from(line.as_str()).unwrap();
+    assert_eq!(parsed.name, med.name);
+    assert_eq!(parsed.dosage, med.dosage);
+    assert_eq!(parsed.notes, med.notes);
+    assert_eq!(parsed.current_count, med.current_count);
+    assert_eq!(parsed.to_csv_line(), line);
+    assert!(Medication::try_from("Aspirin,81mg").is_err());
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+
+fn day(offset: i64) -> String {
+    add_days(&get_today(), offset).unwrap()
+}
+
+#[test]
+fn streak_counts_consecutive_perfect_days() {
+    let mut tracker = common::tracker("streak_counts_consecutive_perfect_days");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-10));
+    
+    tracker.mark_taken("Aspirin", &day(-3), 1, false).unwrap();
+    for offset in -2..=0 {
+        tracker.mark_taken("Aspirin", &day(offset), 1, true).unwrap();
+    }
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 3);
+}
+
+#[test]
+fn paused_days_are_not_perfect_days() {
+    let mut tracker = common::tracker("paused_days_are_not_perfect_days");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-10));
+    tracker.mark_taken("Aspirin", &day(-5), 1, true).unwrap();
+    tracker.set_paused("Aspirin", true).unwrap();
+    
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 0);
+}
+
+#[test]
+fn all_paused_legacy_meds_do_not_hang() {
+    let mut tracker = common::tracker("all_paused_legacy_meds_do_not_hang");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Statin", "Evening", 30);
+    tracker.mark_taken("Aspirin", &get_today(), 1, true).unwrap();
+    tracker.set_paused("Aspirin", true).unwrap();
+    tracker.set_paused("Statin", true).unwrap();
+    let tracker = common::backdate(tracker, "");
+    
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 0);
+}
+
+#[test]
+fn one_missed_dose_breaks_the_streak() {
+    let mut tracker = common::tracker("one_missed_dose_breaks_the_streak");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    common::add(&mut tracker, "Statin", "Evening", 30);
+    let mut tracker = common::backdate(tracker, &day(-10));
+    
+    for offset in -4..=0 {
+        tracker.mark_taken("Aspirin", &day(offset), 1, true).unwrap();
+        tracker.mark_taken("Statin", &day(offset), 1, offset != -2).unwrap();
+    }
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 2);
+}
+
+#[test]
+fn a_medication_added_later_does_not_break_earlier_days() {
+    let mut tracker = common::tracker("a_medication_added_later_does_not_break_earlier_days");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    let mut tracker = common::backdate(tracker, &day(-10));
+    for offset in -2..=0 {
+        tracker.mark_taken("Aspirin", &day(offset), 1, true).unwrap();
+    }
+    common::add(&mut tracker, "Statin", "Evening", 30);
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 2);
+    
+    tracker.mark_taken("Statin", &get_today(), 1, true).unwrap();
+    assert_eq!(tracker.current_streak(&get_today()).unwrap(), 3);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, slugify, MedicationTracker};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// Reopens the tracker after appending a taken first dose of `med_name` at each time, one per
+/// day going back from yesterday.
+fn with_dose_times(tracker: MedicationTracker, med_name: &str, times: &[&str]) -> MedicationTracker {
+    tracker.save_all().unwrap();
+    let dir = tracker.data_dir().to_path_buf();
+    let patient = tracker.patient_name().to_string();
+    drop(tracker);
+    
+    let mut logs = OpenOptions::new().create(true).append(true)
+        .open(dir.join(format!("{}_logs.txt", slugify(&patient))))
+        .unwrap();
+    for (days_back, time) in (1..).zip(times) {
+        writeln!(logs, "{},{},1,1,{},,,1", add_days(&get_today(), -days_back).unwrap(), med_name, time).unwrap();
+    }
+    MedicationTracker::with_data_dir(&patient, &dir).unwrap()
+}
+
+#[test]
+fn doses_around_midnight_average_to_midnight() {
+    let mut tracker = common::tracker("doses_around_midnight_average_to_midnight");
+    common::add(&mut tracker, "Melatonin", "21:00", 30);
+    let tracker = with_dose_times(tracker, "Melatonin", &["23:50:00", "00:10:00", "23:50:00", "00:10:00", "23:55:00", "00:05:00"]);
+    
+    assert_eq!(tracker.suggested_time("Melatonin").as_deref(), Some("00:00"));
+}
+
+#[test]
+fn no_suggestion_near_the_schedule_across_midnight() {
+    let mut tracker = common::tracker("no_suggestion_near_the_schedule_across_midnight");
+    common::add(&mut tracker, "Melatonin", "23:55", 30);
+    let tracker = with_dose_times(tracker, "Melatonin", &["00:05:00"; 5]);
+    
+    assert_eq!(tracker.suggested_time("Melatonin"), None);
+}
+
+#[test]
+fn suggestion_needs_enough_samples() {
+    let mut tracker = common::tracker("suggestion_needs_enough_samples");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let tracker = with_dose_times(tracker, "Lisinopril", &["10:30:00"; 4]);
+    assert_eq!(tracker.suggested_time("Lisinopril"), None);
+    
+    let tracker = with_dose_times(tracker, "Lisinopril", &["10:30:00"; 5]);
+    assert_eq!(tracker.suggested_time("Lisinopril").as_deref(), Some("10:30"));
+}
+
+#[test]
+fn dose_well_after_its_slot_is_late() {
+    let mut tracker = common::tracker("dose_well_after_its_slot_is_late");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let tracker = with_dose_times(tracker, "Lisinopril", &["14:00:00", "08:30:00"]);
+    let yesterday = add_days(&get_today(), -1).unwrap();
+    
+    assert!(tracker.taken_late("Lisinopril", &yesterday));
+    assert!(!tracker.taken_late("Lisinopril", &add_days(&get_today(), -2).unwrap()));
+    let summary = tracker.generate_weekly_summary(&yesterday).unwrap();
+    assert!(summary.contains("[L]"));
+    assert!(summary.contains("On time: "));
+}
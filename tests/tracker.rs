@@ -0,0 +1,222 @@
+mod common;
+
+use medication_tracker::{add_days, get_today, get_week_start, DailyLog, DoseRecord, DoseStatus, Medication, MedicationStatus, MedicationTracker, Priority, Quantity};
+
+#[test]
+fn add_mark_and_summarize() {
+    let mut tracker = common::tracker("add_mark_and_summarize");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    
+    let status = tracker.today_status(&today).unwrap();
+    assert_eq!(status.len(), 1);
+    assert!(status[0].taken);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    
+    let summary = tracker.generate_weekly_summary(&get_week_start()).unwrap();
+    assert!(summary.contains("Test Patient"));
+    assert!(summary.contains("MEDICATION: Lisinopril (10mg)"));
+}
+
+#[test]
+fn data_survives_reopening() {
+    let mut tracker = common::tracker("data_survives_reopening");
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let today = get_today();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.doses_taken("Metformin", &today), 1);
+    assert_eq!(tracker.get_medication("Metformin").unwrap().current_count, Quantity::whole(59));
+}
+
+#[test]
+fn removed_medications_leave_the_summary() {
+    let mut tracker = common::tracker("removed_medications_leave_the_summary");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 30);
+    tracker.mark_taken("Metformin", &get_today(), 1, true).unwrap();
+    
+    tracker.remove_medication("Atorvastatin").unwrap();
+    tracker.remove_medication("Metformin").unwrap();
+    assert!(tracker.remove_medication("Metformin").is_err());
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names(), vec!["Lisinopril".to_string()]);
+    assert_eq!(tracker.doses_taken("Metformin", &get_today()), 0);
+    let summary = tracker.generate_weekly_summary(&get_week_start()).unwrap();
+    assert!(summary.contains("Lisinopril"));
+    assert!(!summary.contains("Metformin"));
+    assert!(!summary.contains("Atorvastatin"));
+}
+
+#[test]
+fn editing_the_time_keeps_counts_and_logs() {
+    let mut tracker = common::tracker("editing_the_time_keeps_counts_and_logs");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    
+    tracker.edit_medication("Lisinopril", None, Some("Evening".to_string())).unwrap();
+    tracker.edit_medication("Lisinopril", Some("20mg".to_string()), None).unwrap();
+    let med = tracker.get_medication("Lisinopril").unwrap();
+    assert_eq!((med.time_of_day.as_str(), med.dosage.as_str()), ("Evening", "20mg"));
+    assert_eq!((med.current_count, med.total_prescribed), (Quantity::whole(29), Quantity::whole(30)));
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    
+    assert!(tracker.edit_medication("Lisinopril", Some(" ".to_string()), None).is_err());
+    assert!(tracker.edit_medication("Aspirin", None, Some("Evening".to_string())).is_err());
+}
+
+#[test]
+fn adding_a_duplicate_name_is_rejected() {
+    let mut tracker = common::tracker("adding_a_duplicate_name_is_rejected");
+    common::add(&mut tracker, "Aspirin", "Morning", 30);
+    tracker.mark_taken("Aspirin", &get_today(), 1, true).unwrap();
+    
+    let err = tracker.add_medication("Aspirin".to_string(), "81mg".to_string(), "Evening".to_string(), 90, 1, String::new()).unwrap_err();
+    assert!(err.contains("already exists"));
+    let med = tracker.get_medication("Aspirin").unwrap();
+    assert_eq!((med.current_count, med.total_prescribed), (Quantity::whole(29), Quantity::whole(30)));
+    assert_eq!(med.dosage, "10mg");
+}
+
+#[test]
+fn switching_patients_keeps_their_medications_apart() {
+    let mut tracker = common::tracker("switching_patients_keeps_their_medications_apart");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    
+    tracker.switch_patient("Second Patient").unwrap();
+    assert_eq!(tracker.patient_name(), "Second Patient");
+    assert!(tracker.medication_names().is_empty());
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    
+    tracker.switch_patient("Test Patient").unwrap();
+    assert_eq!(tracker.medication_names(), ["Lisinopril"]);
+    tracker.switch_patient("Second Patient").unwrap();
+    assert_eq!(tracker.medication_names(), ["Metformin"]);
+    assert!(tracker.switch_patient("  ").is_err());
+    assert_eq!(tracker.patient_name(), "Second Patient");
+}
+
+#[test]
+fn patients_are_registered_once_their_files_are_saved() {
+    let dir = common::data_dir("patients_are_registered_once_their_files_are_saved");
+    let registry = |dir: &std::path::Path| std::fs::read_to_string(dir.join("patients.txt")).unwrap_or_default();
+    
+    drop(MedicationTracker::with_data_dir("Looked Up", &dir).unwrap());
+    let mut tracker = MedicationTracker::with_data_dir("Jo Smith", &dir).unwrap();
+    assert!(MedicationTracker::with_data_dir("Jo Smith", &dir).is_err());
+    assert_eq!(registry(&dir), "");
+    
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.save_all().unwrap();
+    tracker.save_all().unwrap();
+    assert_eq!(registry(&dir), "jo_smith,Jo Smith\n");
+    drop(tracker);
+    
+    let mut namesake = MedicationTracker::with_data_dir("Jo-Smith", &dir).unwrap();
+    assert!(namesake.medication_names().is_empty());
+    common::add(&mut namesake, "Metformin", "Evening", 60);
+    drop(namesake);
+    assert_eq!(registry(&dir), "jo_smith,Jo Smith\njo_smith_2,Jo-Smith\n");
+}
+
+#[test]
+fn display_is_one_canonical_line() {
+    let mut med = Medication::try_from("Aspirin,81mg,Morning,28,30").unwrap();
+    assert_eq!(med.to_string(), "Aspirin - 81mg at Morning, 1x daily (28 left)");
+    med.priority = Priority::High;
+    med.notes = "With food".to_string();
+    assert_eq!(med.to_string(), "Aspirin - 81mg at Morning, 1x daily (28 left) - High priority - Note: With food");
+    
+    let mut log = DailyLog::new("2024-03-02");
+    assert_eq!(log.to_string(), "2024-03-02: 0 taken, 0 missed, 0 skipped across 0 medications");
+    let record = |status| DoseRecord { status, time: None, administered_by: None, deducted: false };
+    log.taken.entry("Aspirin".to_string()).or_default().insert(1, record(DoseStatus::Taken));
+    log.taken.entry("Aspirin".to_string()).or_default().insert(2, record(DoseStatus::Skipped("nausea".to_string())));
+    assert_eq!(log.to_string(), "2024-03-02: 1 taken, 0 missed, 1 skipped across 1 medication");
+}
+
+#[test]
+fn copied_regimen_has_the_meds_but_no_logs() {
+    let mut tracker = common::tracker("copied_regimen_has_the_meds_but_no_logs");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    
+    let copy = tracker.copy_regimen_to("Respite Copy").unwrap();
+    assert_eq!(copy.patient_name(), "Respite Copy");
+    assert_eq!(copy.medication_names(), ["Lisinopril", "Metformin"]);
+    assert_eq!(copy.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+    assert_eq!(copy.doses_taken("Lisinopril", &today), 0);
+    drop(copy);
+    
+    assert!(tracker.copy_regimen_to("Respite Copy").is_err());
+    assert!(tracker.copy_regimen_to("Test Patient").is_err());
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+}
+
+#[test]
+fn renamed_medication_keeps_its_history() {
+    let mut tracker = common::tracker("renamed_medication_keeps_its_history");
+    common::add(&mut tracker, "Lisinoprill", "Morning", 30);
+    common::add(&mut tracker, "Metformin", "Evening", 60);
+    let last_week = add_days(&get_week_start(), -7).unwrap();
+    let mut tracker = common::backdate(tracker, &last_week);
+    for offset in 0..3 {
+        tracker.mark_taken("Lisinoprill", &add_days(&last_week, offset).unwrap(), 1, true).unwrap();
+    }
+    
+    assert!(tracker.rename_medication("Lisinoprill", "Metformin").is_err());
+    assert!(tracker.rename_medication("Aspirin", "Lisinopril").is_err());
+    tracker.rename_medication("Lisinoprill", "Lisinopril").unwrap();
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.medication_names(), ["Lisinopril", "Metformin"]);
+    let summary = tracker.generate_weekly_summary(&last_week).unwrap();
+    assert!(summary.contains("MEDICATION: Lisinopril (10mg)"));
+    assert!(summary.contains("Adherence: 3/7 days"));
+    assert!(!summary.contains("Lisinoprill"));
+}
+
+#[test]
+fn today_status_has_typed_fields() {
+    let mut tracker = common::tracker("today_status_has_typed_fields");
+    tracker.add_medication("Metformin".to_string(), "500mg".to_string(), "Evening".to_string(), 60, 2, String::new()).unwrap();
+    let today = get_today();
+    tracker.mark_taken("Metformin", &today, 1, true).unwrap();
+    
+    let status = tracker.today_status(&today).unwrap();
+    assert!(status[0].last_taken_time.is_some());
+    assert_eq!(status, [MedicationStatus {
+        name: "Metformin".to_string(),
+        dosage: "500mg".to_string(),
+        time_of_day: "Evening".to_string(),
+        priority: Priority::Normal,
+        is_prn: false,
+        doses_taken: 1,
+        doses_per_day: 2,
+        taken: false,
+        reminder_needed: true,
+        snoozed_until: None,
+        last_taken_time: status[0].last_taken_time.clone(),
+        late: false,
+    }]);
+}
+
+#[test]
+fn empty_tracker_summary_says_so() {
+    let tracker = common::tracker("empty_tracker_summary_says_so");
+    let summary = tracker.generate_weekly_summary("2024-01-01").unwrap();
+    
+    assert!(summary.contains("Week starting: 2024-01-01\n"));
+    assert!(summary.contains("No medications on record for this patient.\n"));
+    assert!(!summary.contains("DAILY OVERVIEW"));
+    assert!(!summary.contains("Mon"));
+    assert!(!summary.contains("medications taken"));
+}
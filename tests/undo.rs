@@ -0,0 +1,40 @@
+mod common;
+
+use medication_tracker::{get_today, Quantity};
+
+#[test]
+fn undo_restores_the_log_and_the_count() {
+    let mut tracker = common::tracker("undo_restores_the_log_and_the_count");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+    assert!(tracker.undo_last().unwrap().contains("Lisinopril"));
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 0);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(30));
+    assert!(tracker.undo_last().is_err());
+}
+
+#[test]
+fn undoing_an_unmark_takes_the_dose_back_out() {
+    let mut tracker = common::tracker("undoing_an_unmark_takes_the_dose_back_out");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    let today = get_today();
+    tracker.mark_taken("Lisinopril", &today, 1, true).unwrap();
+    tracker.mark_taken("Lisinopril", &today, 1, false).unwrap();
+    
+    tracker.undo_last().unwrap();
+    assert_eq!(tracker.doses_taken("Lisinopril", &today), 1);
+    assert_eq!(tracker.get_medication("Lisinopril").unwrap().current_count, Quantity::whole(29));
+}
+
+#[test]
+fn undo_after_removal_fails_cleanly() {
+    let mut tracker = common::tracker("undo_after_removal_fails_cleanly");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    tracker.mark_taken("Lisinopril", &get_today(), 1, true).unwrap();
+    tracker.remove_medication("Lisinopril").unwrap();
+    
+    assert!(tracker.undo_last().unwrap_err().contains("no longer being tracked"));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use medication_tracker::{parse_dosage, parse_quantity, parse_quantity_or, Dosage, MedicationTracker};
+
+#[test]
+fn empty_names_and_dosages_are_rejected() {
+    let mut tracker = common::tracker("empty_names_and_dosages_are_rejected");
+    
+    let add = |tracker: &mut MedicationTracker, name: &str, dosage: &str| {
+        tracker.add_medication(name.to_string(), dosage.to_string(), "Morning".to_string(), 30, 1, String::new())
+    };
+    assert!(add(&mut tracker, "", "10mg").unwrap_err().contains("name"));
+    assert!(add(&mut tracker, "   ", "10mg").unwrap_err().contains("name"));
+    assert!(add(&mut tracker, "Aspirin", " ").unwrap_err().contains("Dosage"));
+    assert!(add(&mut tracker, "Aspirin", "-5mg").is_err());
+    assert!(tracker.medication_names().is_empty());
+}
+
+#[test]
+fn non_numeric_quantities_are_errors() {
+    assert_eq!(parse_quantity(" 45 "), Ok(45));
+    assert!(parse_quantity("3O").unwrap_err().contains("'3O' is not a valid quantity"));
+    assert!(parse_quantity("-5").is_err());
+    assert!(parse_quantity("").is_err());
+    assert_eq!(parse_quantity_or("", 30), Ok(30));
+    assert!(parse_quantity_or("thirty", 30).is_err());
+}
+
+#[test]
+fn dosages_parse_into_amount_and_unit() {
+    assert_eq!(parse_dosage("5ml"), Ok(Dosage { amount: 5.0, unit: "ml".to_string() }));
+    assert_eq!(parse_dosage(" 10 mg "), Ok(Dosage { amount: 10.0, unit: "mg".to_string() }));
+    assert_eq!(parse_dosage("2 Tablets").unwrap().to_string(), "2 tablets");
+    assert!(parse_dosage("abc").is_err());
+    assert!(parse_dosage("-5 mg").is_err());
+    assert!(parse_dosage("10").is_err());
+    assert!(parse_dosage("10 spoons").is_err());
+}
+
+#[test]
+fn blank_quantity_uses_the_configured_default() {
+    let mut tracker = common::tracker("blank_quantity_uses_the_configured_default");
+    assert_eq!(tracker.default_quantity(), 30);
+    tracker.set_default_quantity(90).unwrap();
+    assert!(tracker.set_default_quantity(0).is_err());
+    
+    let tracker = common::reopen(tracker);
+    assert_eq!(tracker.default_quantity(), 90);
+    assert_eq!(parse_quantity_or("  ", tracker.default_quantity()), Ok(90));
+    assert!(parse_quantity_or("ninety", tracker.default_quantity()).is_err());
+}
+
+#[test]
+fn parsed_dosages_are_kept_on_the_medication() {
+    let mut tracker = common::tracker("parsed_dosages_are_kept_on_the_medication");
+    common::add(&mut tracker, "Metformin", "Morning", 30);
+    tracker.add_medication("Cough Syrup".to_string(), "two spoons".to_string(), "Evening".to_string(), 30, 1, String::new()).unwrap();
+    let mut tracker = common::reopen(tracker);
+    
+    let mg = Dosage { amount: 10.0, unit: "mg".to_string() };
+    assert_eq!(tracker.get_medication("Metformin").unwrap().parsed_dosage, Some(mg));
+    assert_eq!(tracker.unrecognized_dosages(), ["Cough Syrup"]);
+    
+    tracker.edit_medication("Cough Syrup", Some("5 ml".to_string()), None).unwrap();
+    assert_eq!(tracker.get_medication("Cough Syrup").unwrap().parsed_dosage, Some(Dosage { amount: 5.0, unit: "ml".to_string() }));
+    assert!(tracker.unrecognized_dosages().is_empty());
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use medication_tracker::{add_days, get_today};
+
+#[test]
+fn weekly_csv_leaves_unscheduled_days_blank() {
+    let mut tracker = common::tracker("weekly_csv_leaves_unscheduled_days_blank");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Methotrexate", "Morning", 4);
+    tracker.set_frequency("Methotrexate", "Mon".parse().unwrap()).unwrap();
+    let today = get_today();
+    let monday = add_days(&tracker.week_start(&today), -7).unwrap();
+    let mut tracker = common::backdate(tracker, &add_days(&monday, -7).unwrap());
+    tracker.mark_taken("Methotrexate", &monday, 1, true).unwrap();
+    for offset in [0, 1, 3] {
+        tracker.mark_taken("Lisinopril", &add_days(&monday, offset).unwrap(), 1, true).unwrap();
+    }
+    
+    let csv = std::fs::read_to_string(tracker.export_weekly_csv(&monday).unwrap()).unwrap();
+    assert_eq!(csv, "Medication,Mon,Tue,Wed,Thu,Fri,Sat,Sun,Adherence%,Remaining\n\
+                     Lisinopril,1,1,0,1,0,0,0,42.9,27\n\
+                     Methotrexate,1,,,,,,,100.0,3\n");
+}
+
+#[test]
+fn weekly_medication_is_not_missed_on_other_days() {
+    let mut tracker = common::tracker("weekly_medication_is_not_missed_on_other_days");
+    common::add(&mut tracker, "Methotrexate", "Morning", 4);
+    tracker.set_frequency("Methotrexate", "Mon".parse().unwrap()).unwrap();
+    let monday = add_days(&tracker.week_start(&get_today()), -7).unwrap();
+    let tracker = common::backdate(tracker, &add_days(&monday, -7).unwrap());
+    
+    assert_eq!(tracker.get_missed_medications(&monday).unwrap().len(), 1);
+    assert!(tracker.get_missed_medications(&add_days(&monday, 1).unwrap()).unwrap().is_empty());
+}
+
+#[test]
+fn weekly_csv_has_a_row_per_medication() {
+    let mut tracker = common::tracker("weekly_csv_has_a_row_per_medication");
+    common::add(&mut tracker, "Lisinopril", "Morning", 30);
+    common::add(&mut tracker, "Atorvastatin", "Bedtime", 30);
+    let monday = add_days(&tracker.week_start(&get_today()), -7).unwrap();
+    let tracker = common::backdate(tracker, &add_days(&monday, -7).unwrap());
+    
+    let path = tracker.export_weekly_csv(&monday).unwrap();
+    assert!(path.ends_with(&format!("test_patient_weekly_report_{}.csv", monday)));
+    let csv = std::fs::read_to_string(path).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1], "Atorvastatin,0,0,0,0,0,0,0,0.0,30");
+    assert_eq!(rows[2], "Lisinopril,0,0,0,0,0,0,0,0.0,30");
+}